@@ -0,0 +1,172 @@
+//! A minimal radix-2 fast Fourier transform for the DSP processors.
+use crate::Sample;
+use alloc::vec::Vec;
+use core::ops::{Add, Mul};
+use micromath::F32Ext;
+
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub struct Complex {
+    pub re: f32,
+    pub im: f32,
+}
+
+impl Complex {
+    pub const fn new(re: f32, im: f32) -> Self {
+        Self { re, im }
+    }
+
+    const fn conj(self) -> Self {
+        Self {
+            re: self.re,
+            im: -self.im,
+        }
+    }
+}
+
+impl Add for Complex {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self {
+            re: self.re + rhs.re,
+            im: self.im + rhs.im,
+        }
+    }
+}
+
+impl Mul for Complex {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        Self {
+            re: F32Ext::mul_add(self.re, rhs.re, -self.im * rhs.im),
+            im: F32Ext::mul_add(self.re, rhs.im, self.im * rhs.re),
+        }
+    }
+}
+
+/// Precomputed tables for the FFT of a fixed size.
+pub struct Fft {
+    /// `exp(-2πik/N)` for each `k` in the first half of the spectrum.
+    twiddles: Vec<Complex>,
+    /// The bit-reversed index for each index.
+    reversed: Vec<usize>,
+}
+
+impl Fft {
+    /// Prepare the FFT for the given size, which must be a power of two.
+    pub fn new(size: usize) -> Self {
+        debug_assert!(size.is_power_of_two());
+        let bits = size.trailing_zeros();
+        let reversed = (0..size)
+            .map(|i| {
+                i.reverse_bits()
+                    .checked_shr(usize::BITS - bits)
+                    .unwrap_or(0)
+            })
+            .collect();
+
+        // Calculate the twiddles with SIMD because its sin/cos are more precise
+        // than the ones in micromath, and the FFT errors accumulate.
+        let mut twiddles = Vec::with_capacity(size / 2);
+        let step = -core::f32::consts::TAU / size as f32;
+        for chunk in 0..size.div_ceil(16) {
+            let first = (chunk * 8) as f32;
+            let angles = Sample::new([
+                first,
+                first + 1.,
+                first + 2.,
+                first + 3.,
+                first + 4.,
+                first + 5.,
+                first + 6.,
+                first + 7.,
+            ]) * step;
+            let (sin, cos) = angles.sin_cos();
+            for (sin, cos) in sin.as_array().iter().zip(cos.as_array()) {
+                if twiddles.len() < size / 2 {
+                    twiddles.push(Complex::new(*cos, *sin));
+                }
+            }
+        }
+        Self { twiddles, reversed }
+    }
+
+    /// Transform the signal into its spectrum, in place.
+    pub fn forward(&self, buf: &mut [Complex]) {
+        debug_assert_eq!(buf.len(), self.reversed.len());
+        for (i, j) in self.reversed.iter().enumerate() {
+            if i < *j {
+                buf.swap(i, *j);
+            }
+        }
+        let size = buf.len();
+        let mut half = 1;
+        while half < size {
+            let stride = size / (half * 2);
+            for start in (0..size).step_by(half * 2) {
+                for k in 0..half {
+                    let w = self.twiddles[k * stride];
+                    let a = buf[start + k];
+                    let b = buf[start + k + half] * w;
+                    buf[start + k] = a + b;
+                    buf[start + k + half] = Complex::new(a.re - b.re, a.im - b.im);
+                }
+            }
+            half *= 2;
+        }
+    }
+
+    /// Transform the spectrum back into the signal, in place.
+    ///
+    /// The result is not normalized: it's scaled up by the FFT size.
+    pub fn inverse(&self, buf: &mut [Complex]) {
+        for c in buf.iter_mut() {
+            *c = c.conj();
+        }
+        self.forward(buf);
+        for c in buf.iter_mut() {
+            *c = c.conj();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let fft = Fft::new(16);
+        let input: Vec<Complex> = (0..16)
+            .map(|i| Complex::new(i as f32, (i % 3) as f32))
+            .collect();
+        let mut buf = input.clone();
+        fft.forward(&mut buf);
+        // The first bin is the sum of all inputs.
+        assert!((buf[0].re - 120.).abs() < 1e-3);
+        assert!((buf[0].im - 15.).abs() < 1e-3);
+        fft.inverse(&mut buf);
+        for (a, b) in input.iter().zip(&buf) {
+            assert!((a.re - b.re / 16.).abs() < 1e-4, "{a:?} != {b:?}");
+            assert!((a.im - b.im / 16.).abs() < 1e-4, "{a:?} != {b:?}");
+        }
+    }
+
+    #[test]
+    fn single_frequency() {
+        let fft = Fft::new(32);
+        let mut buf: Vec<Complex> = (0..32)
+            .map(|i| {
+                let angle = core::f32::consts::TAU * 3. * i as f32 / 32.;
+                Complex::new(angle.cos(), angle.sin())
+            })
+            .collect();
+        fft.forward(&mut buf);
+        for (i, c) in buf.iter().enumerate() {
+            let expected = if i == 3 { 32. } else { 0. };
+            assert!((c.re - expected).abs() < 1e-3, "bin {i}: {c:?}");
+            assert!(c.im.abs() < 1e-3, "bin {i}: {c:?}");
+        }
+    }
+}
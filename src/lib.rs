@@ -16,6 +16,7 @@ extern crate alloc;
 
 mod basic_types;
 mod error;
+mod fft;
 mod manager;
 pub mod modulators;
 mod node;
@@ -8,6 +8,8 @@ pub struct Manager {
     paths: Vec<Box<[u8]>>,
     prev: Option<Frame>,
     consumed: usize,
+    dc_blocker: Option<DcBlocker>,
+    limiter: Option<Limiter>,
 }
 
 impl Manager {
@@ -18,6 +20,8 @@ impl Manager {
             paths: vec![Box::new([])],
             prev: None,
             consumed: 0,
+            dc_blocker: None,
+            limiter: None,
         }
     }
 
@@ -81,6 +85,22 @@ impl Manager {
         Ok(())
     }
 
+    /// Apply the given [`Limiter`] to the mix before converting it to PCM.
+    ///
+    /// Without a limiter, samples going above the full scale are hard-clipped.
+    /// Pass `None` to remove the limiter.
+    pub fn set_limiter(&mut self, limiter: Option<Limiter>) {
+        self.limiter = limiter;
+    }
+
+    /// Apply the given [`DcBlocker`] to the mix before converting it to PCM.
+    ///
+    /// If there is also a limiter, the DC offset is removed before limiting.
+    /// Pass `None` to remove the DC blocker.
+    pub const fn set_dc_blocker(&mut self, dc_blocker: Option<DcBlocker>) {
+        self.dc_blocker = dc_blocker;
+    }
+
     /// Fill the given buffer with PCM sound values.
     pub fn write(&mut self, buf: &mut [i16]) {
         // If there is partially emitted frame left from the previous write iteration,
@@ -88,7 +108,7 @@ impl Manager {
         let mut buf = self.write_prev(buf);
 
         while buf.len() >= 16 {
-            let Some(frame) = self.root.next_frame() else {
+            let Some(frame) = self.next_frame() else {
                 break;
             };
             let written = fill_buf(buf, &frame, 0);
@@ -99,7 +119,7 @@ impl Manager {
         // the next frame into it. The rest of the frame will be written
         // on the next write loop.
         if !buf.is_empty() {
-            if let Some(frame) = self.root.next_frame() {
+            if let Some(frame) = self.next_frame() {
                 self.prev = Some(frame);
                 self.consumed = 0;
                 buf = self.write_prev(buf);
@@ -112,6 +132,18 @@ impl Manager {
         }
     }
 
+    /// Get the next frame of the mix with the master bus processing applied.
+    fn next_frame(&mut self) -> Option<Frame> {
+        let mut frame = self.root.next_frame();
+        if let Some(dc_blocker) = &mut self.dc_blocker {
+            frame = frame.and_then(|f| dc_blocker.process_frame(f));
+        }
+        match &mut self.limiter {
+            Some(limiter) => limiter.next(frame),
+            None => frame,
+        }
+    }
+
     #[must_use]
     fn write_prev<'a>(&mut self, buf: &'a mut [i16]) -> &'a mut [i16] {
         debug_assert!(self.consumed < 16);
@@ -199,6 +231,192 @@ mod tests {
         assert_eq!(&buf[16..], &[0, 0, 0, 0]);
     }
 
+    #[test]
+    fn test_limiter() {
+        let mut manager = Manager::new();
+        for _ in 0..2 {
+            let node = Box::new(Sine::new(440., 0.));
+            manager.add_node(0, node).ok().unwrap();
+        }
+        let mut buf = [0i16; 4096];
+        manager.write(&mut buf);
+        assert!(buf.contains(&i16::MAX));
+
+        manager.set_limiter(Some(Limiter::new(-6., 128, 4410.)));
+        manager.write(&mut buf);
+        let max = buf.iter().map(|s| s.unsigned_abs()).max().unwrap();
+        assert!(max > 14_000, "{max}");
+        assert!(max < 16_500, "{max}");
+    }
+
+    #[test]
+    fn test_dc_blocker() {
+        let mut manager = Manager::new();
+        // A square wave with zero frequency is a constant -1.
+        let node = Box::new(Square::new(0., 0.));
+        manager.add_node(0, node).ok().unwrap();
+        manager.set_dc_blocker(Some(DcBlocker::new(20.)));
+        let mut buf = vec![0i16; 2 * SAMPLE_RATE as usize];
+        manager.write(&mut buf);
+        assert_eq!(buf[0], -i16::MAX);
+        let tail = &buf[buf.len() - 100..];
+        assert!(tail.iter().all(|s| s.abs() < 10), "{tail:?}");
+    }
+
+    /// A sound source producing a constant value for the given number of frames.
+    struct Finite {
+        frames: u32,
+        value: f32,
+    }
+
+    impl Finite {
+        const fn new(frames: u32, value: f32) -> Self {
+            Self { frames, value }
+        }
+    }
+
+    impl Processor for Finite {
+        fn process_children(&mut self, _cn: &mut [Node]) -> Option<Frame> {
+            if self.frames == 0 {
+                return None;
+            }
+            self.frames -= 1;
+            Some(Frame::mono(Sample::splat(self.value)))
+        }
+    }
+
+    /// Get the number of samples (per channel) before the last non-zero sample.
+    fn played(buf: &[i16]) -> usize {
+        buf.iter().rposition(|s| *s != 0).map_or(0, |i| i / 2 + 1)
+    }
+
+    #[test]
+    fn test_speed() {
+        for speed in [0.5, 1., 2., 3.] {
+            let mut manager = Manager::new();
+            let id = manager
+                .add_node(0, Box::new(Speed::new(speed)))
+                .ok()
+                .unwrap();
+            manager
+                .add_node(id, Box::new(Finite::new(100, 0.5)))
+                .ok()
+                .unwrap();
+            let mut buf = vec![0i16; 4096];
+            manager.write(&mut buf);
+            let expected = 800. / speed;
+            let actual = played(&buf) as f32;
+            assert!((actual - expected).abs() <= 8., "{speed}: {actual}");
+        }
+    }
+
+    #[test]
+    fn test_speed_stop() {
+        let mut manager = Manager::new();
+        let id = manager.add_node(0, Box::new(Speed::new(1.))).ok().unwrap();
+        manager
+            .add_node(id, Box::new(Sine::new(100., 0.)))
+            .ok()
+            .unwrap();
+        let mut buf = [0i16; 1024];
+        manager.write(&mut buf);
+        assert!(buf.iter().any(|s| s.abs() > 10_000));
+        manager.get_node(id).ok().unwrap().set(0, 0.);
+        manager.write(&mut buf);
+        assert!(buf.iter().all(|s| *s == 0));
+    }
+
+    #[test]
+    fn test_time_stretch() {
+        for tempo in [0.5, 1., 2.] {
+            let mut manager = Manager::new();
+            let node = Box::new(TimeStretch::new(tempo));
+            let id = manager.add_node(0, node).ok().unwrap();
+            manager
+                .add_node(id, Box::new(Finite::new(1000, 0.5)))
+                .ok()
+                .unwrap();
+            let mut buf = vec![0i16; 40_000];
+            manager.write(&mut buf);
+            // The output can be longer by up to one segment.
+            let expected = 8000. / tempo;
+            let actual = played(&buf) as f32;
+            assert!(actual >= expected, "{tempo}: {actual}");
+            assert!(actual <= expected + 512., "{tempo}: {actual}");
+        }
+    }
+
+    #[test]
+    fn test_ring_mod_without_carrier() {
+        let mut expected = [0i16; 256];
+        let mut manager = Manager::new();
+        manager
+            .add_node(0, Box::new(Sine::new(440., 0.)))
+            .ok()
+            .unwrap();
+        manager.write(&mut expected);
+
+        let mut actual = [0i16; 256];
+        let mut manager = Manager::new();
+        let id = manager
+            .add_node(0, Box::new(RingMod::new(0.)))
+            .ok()
+            .unwrap();
+        manager
+            .add_node(id, Box::new(Sine::new(440., 0.)))
+            .ok()
+            .unwrap();
+        manager.write(&mut actual);
+        assert_eq!(actual, expected);
+    }
+
+    /// Play two finite children (160 samples each) through the given `Concat`.
+    fn concat_left(concat: Concat) -> Vec<i16> {
+        let mut manager = Manager::new();
+        let id = manager.add_node(0, Box::new(concat)).ok().unwrap();
+        manager
+            .add_node(id, Box::new(Finite::new(20, 0.5)))
+            .ok()
+            .unwrap();
+        manager
+            .add_node(id, Box::new(Finite::new(20, -0.5)))
+            .ok()
+            .unwrap();
+        let mut buf = vec![0i16; 1024];
+        manager.write(&mut buf);
+        let played = played(&buf);
+        buf.iter().step_by(2).take(played).copied().collect()
+    }
+
+    #[test]
+    fn test_concat() {
+        let left = concat_left(Concat::new());
+        assert_eq!(left.len(), 320);
+        assert!(left[..160].iter().all(|s| *s == 16383));
+        assert!(left[160..].iter().all(|s| *s == -16383));
+    }
+
+    #[test]
+    fn test_concat_crossfade() {
+        let left = concat_left(Concat::new_crossfade(64));
+        assert_eq!(left.len(), 320 - 64);
+        assert_eq!(left[0], 0);
+        assert!(left[1] > 0 && left[1] < 1000, "{}", left[1]);
+        assert_eq!(left[80], 16383);
+        assert_eq!(left[170], -16383);
+    }
+
+    #[test]
+    fn test_concat_gap() {
+        let mut concat = Concat::new();
+        concat.set(3, 40.);
+        let left = concat_left(concat);
+        assert_eq!(left.len(), 320 + 40);
+        assert!(left[..160].iter().all(|s| *s == 16383));
+        assert!(left[160..200].iter().all(|s| *s == 0));
+        assert!(left[200..].iter().all(|s| *s == -16383));
+    }
+
     #[expect(clippy::cast_lossless)]
     fn u2f(u: u16) -> f32 {
         u as f32 / 100.
@@ -1,6 +1,8 @@
 //! A collection of modulators.
 
 use crate::SAMPLE_DURATION;
+use alloc::rc::Rc;
+use core::cell::Cell;
 use micromath::F32Ext;
 
 /// An audio node parameter modulator.
@@ -238,6 +240,27 @@ impl Modulator for Adsr {
     }
 }
 
+/// The level of audio measured by an [`EnvelopeFollower`][crate::EnvelopeFollower] node.
+///
+/// Unlike other modulators, it doesn't depend on time but on the audio
+/// passing through another node. Created by
+/// [`EnvelopeFollower::modulator`][crate::EnvelopeFollower::modulator].
+pub struct Follower {
+    level: Rc<Cell<f32>>,
+}
+
+impl Follower {
+    pub(crate) const fn new(level: Rc<Cell<f32>>) -> Self {
+        Self { level }
+    }
+}
+
+impl Modulator for Follower {
+    fn get(&self, _now: u32) -> f32 {
+        self.level.get().clamp(0., 1.)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     #![allow(clippy::float_cmp)]
@@ -337,4 +360,21 @@ mod tests {
         assert_eq!(lfo.get(41), 0.);
         assert_eq!(lfo.get(50), 0.);
     }
+
+    #[test]
+    fn follower() {
+        use crate::*;
+        let mut follower = EnvelopeFollower::new(0., 1000.);
+        let lfo = follower.modulator();
+        assert_eq!(lfo.get(0), 0.);
+        let frame = Frame::stereo(Sample::splat(0.2), Sample::splat(-0.5));
+        follower.process_frame(frame);
+        assert_eq!(lfo.get(0), 0.5);
+        assert_eq!(lfo.get(100), 0.5);
+        follower.process_frame(Frame::mono(Sample::ZERO));
+        assert!(lfo.get(0) < 0.5);
+        assert!(lfo.get(0) > 0.4);
+        follower.reset();
+        assert_eq!(lfo.get(0), 0.);
+    }
 }
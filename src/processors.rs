@@ -1,4 +1,10 @@
+use crate::fft::{Complex, Fft};
 use crate::*;
+use alloc::collections::VecDeque;
+use alloc::rc::Rc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cell::Cell;
 use micromath::F32Ext;
 
 /// Do nothing: only mix the children and pass the mix forward with no changes.
@@ -91,28 +97,203 @@ impl Processor for Loop {
 }
 
 /// Play children in order, one at a time.
-pub struct Concat {}
+///
+/// Each child can fade in at its start and fade out at its end. Consecutive children
+/// can overlap (the next child starts before the previous one ends) or be separated
+/// by a gap of silence.
+///
+/// Fade-out and overlap require knowing in advance when the child ends,
+/// so the current child is pulled ahead by the longer of the two.
+/// This adds latency: changes to the children (params, pause, etc.)
+/// are heard that much later. To limit the latency and memory usage,
+/// both are capped at a quarter of a second. Params (all in samples):
+///
+/// 0. Fade-in duration.
+/// 1. Fade-out duration, up to [`Concat::MAX_LOOKAHEAD`].
+/// 2. Overlap: for how long the start of the next child is mixed with
+///    the end of the previous one. Up to [`Concat::MAX_LOOKAHEAD`].
+/// 3. Gap: duration of silence between children.
+pub struct Concat {
+    fade_in: Position,
+    fade_out: Position,
+    overlap: Position,
+    gap: Position,
+    /// The index of the child being played.
+    current: usize,
+    /// How many samples have been pulled from the current child.
+    pulled: Position,
+    /// The frames pulled ahead from the current child.
+    queue: VecDeque<Frame>,
+    /// The remaining frames of the previous child, already faded out.
+    tail: VecDeque<Frame>,
+    /// How many frames of silence are left before starting the current child.
+    wait: Position,
+}
 
 impl Concat {
-    // TODO: support fade-in/fade-out
+    /// The longest supported fade-out and overlap (in samples).
+    pub const MAX_LOOKAHEAD: Position = SAMPLE_RATE / 4;
+
     #[must_use]
     pub const fn new() -> Self {
-        Self {}
+        Self {
+            fade_in: 0,
+            fade_out: 0,
+            overlap: 0,
+            gap: 0,
+            current: 0,
+            pulled: 0,
+            queue: VecDeque::new(),
+            tail: VecDeque::new(),
+            wait: 0,
+        }
+    }
+
+    /// Crossfade consecutive children over the given duration (in samples).
+    ///
+    /// The duration is capped at [`Concat::MAX_LOOKAHEAD`].
+    #[must_use]
+    pub const fn new_crossfade(duration: Position) -> Self {
+        let duration = if duration > Self::MAX_LOOKAHEAD {
+            Self::MAX_LOOKAHEAD
+        } else {
+            duration
+        };
+        let mut res = Self::new();
+        res.fade_in = duration;
+        res.fade_out = duration;
+        res.overlap = duration;
+        res
+    }
+
+    /// How many frames of the current child to pull ahead.
+    fn lookahead(&self) -> usize {
+        self.fade_out.max(self.overlap).div_ceil(8) as usize
+    }
+
+    /// Pull the next frame of the current child into the queue.
+    ///
+    /// If the child has ended, move the queue into the tail and switch
+    /// to the next child. In that case, returns false.
+    fn pull(&mut self, cn: &mut [Node]) -> bool {
+        let Some(node) = cn.get_mut(self.current) else {
+            return false;
+        };
+        if let Some(mut f) = node.next_frame() {
+            if self.pulled < self.fade_in {
+                let mut gains = [0f32; 8];
+                for (i, gain) in gains.iter_mut().enumerate() {
+                    let t = (self.pulled as f32 + i as f32) / self.fade_in as f32;
+                    *gain = crossfade_gains(t).1;
+                }
+                f = scale_frame(&f, Sample::new(gains));
+            }
+            self.pulled += 8;
+            self.queue.push_back(f);
+            return true;
+        }
+
+        let total = self.queue.len() * 8;
+        for (i, f) in self.queue.drain(..).enumerate() {
+            let f = if self.fade_out == 0 {
+                f
+            } else {
+                let mut gains = [0f32; 8];
+                for (j, gain) in gains.iter_mut().enumerate() {
+                    let left = (total - i * 8 - j) as f32;
+                    *gain = crossfade_gains(left / self.fade_out as f32).1;
+                }
+                scale_frame(&f, Sample::new(gains))
+            };
+            match self.tail.get_mut(i) {
+                Some(t) => *t = t.clone() + f,
+                None => self.tail.push_back(f),
+            }
+        }
+        self.current += 1;
+        self.pulled = 0;
+        if self.current < cn.len() {
+            self.wait = self.gap.div_ceil(8);
+        }
+        false
     }
 }
 
 impl Processor for Concat {
+    fn reset(&mut self) {
+        self.current = 0;
+        self.pulled = 0;
+        self.queue.clear();
+        self.tail.clear();
+        self.wait = 0;
+    }
+
+    fn set(&mut self, param: u8, val: f32) {
+        #[expect(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let val = val.max(0.) as Position;
+        match param {
+            0 => self.fade_in = val,
+            1 => self.fade_out = val.min(Self::MAX_LOOKAHEAD),
+            2 => self.overlap = val.min(Self::MAX_LOOKAHEAD),
+            3 => self.gap = val,
+            _ => {}
+        }
+    }
+
     fn process_children(&mut self, cn: &mut [Node]) -> Option<Frame> {
-        for node in cn {
-            if let Some(f) = node.next_frame() {
-                return Some(f);
+        let lookahead = self.lookahead();
+        let overlap = self.overlap.div_ceil(8) as usize;
+        let mut active = false;
+        while self.current < cn.len() && self.tail.len() <= overlap {
+            if self.wait > 0 {
+                if self.tail.is_empty() {
+                    self.wait -= 1;
+                    return Some(Frame::zero());
+                }
+                break;
+            }
+            // If the look-ahead got shorter, play the excess without pulling.
+            if self.queue.len() > lookahead {
+                active = true;
+                break;
+            }
+            // Pull up to two frames at once until the queue is filled,
+            // so that the look-ahead doesn't delay the start of the child.
+            active = self.pull(cn);
+            if active && self.queue.len() <= lookahead {
+                active = self.pull(cn);
+            }
+            if active {
+                break;
             }
         }
-        None
+        let prev = self.tail.pop_front();
+        let next = if active { self.queue.pop_front() } else { None };
+        match (prev, next) {
+            (Some(a), Some(b)) => Some(a + b),
+            (a, b) => a.or(b),
+        }
     }
 }
 
+/// Position the sound between the left and the right speaker.
+///
+/// Mono input is converted into stereo using equal-power panning.
+/// Stereo input is handled depending on the mode:
+///
+/// * By default, each channel is scaled by its weight, the same way as mono input.
+/// * In the rotate mode, each input channel is treated as a separate source
+///   placed at its own side, and both sources are moved together.
+///   A source can't go past the edge, so the stereo image gets narrower
+///   as it moves towards the side. See [`Pan::new_rotate`].
+///
+/// Params:
+///
+/// 0. Pan: -1 is left, 0 is center, 1 is right.
+/// 1. Rotate mode: if 1, rotate the stereo image instead of scaling channels.
 pub struct Pan {
+    position: f32,
+    rotate: bool,
     left_weight: f32,
     right_weight: f32,
 }
@@ -122,10 +303,24 @@ impl Pan {
     pub fn new(v: f32) -> Self {
         let (left_weight, right_weight) = pan_weights(v);
         Self {
+            position: v,
+            rotate: false,
             left_weight,
             right_weight,
         }
     }
+
+    /// Pan stereo input by moving the whole stereo image.
+    ///
+    /// For example, with pan 0.5, the left channel is played halfway
+    /// between the left side and the center, and the right channel stays
+    /// on the right. With pan 1, the left channel is played in the center.
+    #[must_use]
+    pub fn new_rotate(v: f32) -> Self {
+        let mut res = Self::new(v);
+        res.rotate = true;
+        res
+    }
 }
 
 #[inline]
@@ -138,15 +333,32 @@ fn pan_weights(v: f32) -> (f32, f32) {
 
 impl Processor for Pan {
     fn set(&mut self, param: u8, val: f32) {
-        if param == 0 {
-            (self.left_weight, self.right_weight) = pan_weights(val);
+        match param {
+            0 => {
+                self.position = val;
+                (self.left_weight, self.right_weight) = pan_weights(val);
+            }
+            1 => self.rotate = val >= 0.5,
+            _ => {}
         }
     }
 
     fn process_frame(&mut self, f: Frame) -> Option<Frame> {
-        let left = f.left * self.left_weight;
-        let right = f.right.map(|s| s * self.right_weight);
-        Some(Frame { left, right })
+        let Some(right) = f.right else {
+            let left = f.left * self.left_weight;
+            let right = f.left * self.right_weight;
+            return Some(Frame::stereo(left, right));
+        };
+        if !self.rotate {
+            let left = f.left * self.left_weight;
+            let right = right * self.right_weight;
+            return Some(Frame::stereo(left, right));
+        }
+        let (left_to_left, left_to_right) = pan_weights(self.position - 1.);
+        let (right_to_left, right_to_right) = pan_weights(self.position + 1.);
+        let left = f.left * left_to_left + right * right_to_left;
+        let right = f.left * left_to_right + right * right_to_right;
+        Some(Frame::stereo(left, right))
     }
 }
 
@@ -252,6 +464,13 @@ impl Processor for TrackPosition {
 }
 
 /// Low-pass/high-pass filter.
+///
+/// Params:
+///
+/// 0. Cutoff frequency (in Hz).
+/// 1. Q factor (resonance).
+///
+/// For other filter types, see [`Biquad`].
 #[derive(Default)]
 pub struct LowHighPass {
     low: bool,
@@ -326,12 +545,16 @@ impl Processor for LowHighPass {
         self.x_n1 = Sample::ZERO;
     }
 
+    #[expect(clippy::float_cmp)]
     fn set(&mut self, param: u8, val: f32) {
-        #[expect(clippy::float_cmp)]
         if param == 0 && val != self.freq {
             self.freq = val;
             self.update_coefs();
         }
+        if param == 1 && val != self.q {
+            self.q = val;
+            self.update_coefs();
+        }
     }
 
     fn process_sample(&mut self, s: Sample) -> Option<Sample> {
@@ -415,6 +638,139 @@ impl Processor for Swap {
     }
 }
 
+/// Make the stereo image narrower or wider by scaling the side (L-R) signal.
+///
+/// Mono input stays mono. Params:
+///
+/// 0. Width: 0 is mono, 1 is unchanged, above 1 is wider than the original.
+pub struct Width {
+    width: f32,
+}
+
+impl Width {
+    #[must_use]
+    pub const fn new(width: f32) -> Self {
+        Self { width }
+    }
+}
+
+impl Processor for Width {
+    fn set(&mut self, param: u8, val: f32) {
+        if param == 0 {
+            self.width = val.max(0.);
+        }
+    }
+
+    fn process_frame(&mut self, f: Frame) -> Option<Frame> {
+        let Some(right) = f.right else {
+            return Some(f);
+        };
+        let mid = (f.left + right) * 0.5;
+        let side = (f.left - right) * (0.5 * self.width);
+        Some(Frame::stereo(mid + side, mid - side))
+    }
+}
+
+/// Convert left/right stereo into mid (left channel) and side (right channel).
+///
+/// Process the mid and side separately and then convert them back
+/// using [`MidSideDecode`].
+pub struct MidSideEncode {}
+
+impl MidSideEncode {
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {}
+    }
+}
+
+impl Processor for MidSideEncode {
+    fn process_frame(&mut self, f: Frame) -> Option<Frame> {
+        let Some(right) = f.right else {
+            return Some(Frame::stereo(f.left, Sample::ZERO));
+        };
+        let mid = (f.left + right) * 0.5;
+        let side = (f.left - right) * 0.5;
+        Some(Frame::stereo(mid, side))
+    }
+}
+
+/// Convert mid (left channel) and side (right channel) back into left/right stereo.
+///
+/// The reverse of [`MidSideEncode`]. Mono input is treated as mid with no side.
+pub struct MidSideDecode {}
+
+impl MidSideDecode {
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {}
+    }
+}
+
+impl Processor for MidSideDecode {
+    fn process_frame(&mut self, f: Frame) -> Option<Frame> {
+        let Some(side) = f.right else {
+            return Some(f);
+        };
+        Some(Frame::stereo(f.left + side, f.left - side))
+    }
+}
+
+/// Make one channel quieter to shift the sound to the other side.
+///
+/// Unlike [`Pan`], the louder channel is never changed. Mono input
+/// is converted into stereo. Params:
+///
+/// 0. Balance: -1 is only left, 0 is unchanged, 1 is only right.
+pub struct Balance {
+    balance: f32,
+}
+
+impl Balance {
+    #[must_use]
+    pub const fn new(balance: f32) -> Self {
+        Self {
+            balance: balance.clamp(-1., 1.),
+        }
+    }
+}
+
+impl Processor for Balance {
+    fn set(&mut self, param: u8, val: f32) {
+        if param == 0 {
+            self.balance = val.clamp(-1., 1.);
+        }
+    }
+
+    fn process_frame(&mut self, f: Frame) -> Option<Frame> {
+        let right = f.right.unwrap_or(f.left);
+        let left_gain = (1. - self.balance).min(1.);
+        let right_gain = (1. + self.balance).min(1.);
+        Some(Frame::stereo(f.left * left_gain, right * right_gain))
+    }
+}
+
+/// Fold stereo into mono by averaging both channels.
+///
+/// Unlike [`TakeLeft`] and [`TakeRight`], nothing panned to one side is lost.
+pub struct MonoSum {}
+
+impl MonoSum {
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {}
+    }
+}
+
+impl Processor for MonoSum {
+    fn process_frame(&mut self, f: Frame) -> Option<Frame> {
+        let Some(right) = f.right else {
+            return Some(f);
+        };
+        Some(Frame::mono((f.left + right) * 0.5))
+    }
+}
+
 /// Clamp the amplitude onto the given interval
 pub struct Clip {
     low: f32,
@@ -449,14 +805,3482 @@ impl Processor for Clip {
     }
 }
 
-// TODO: DelayLeft
-// TODO: DelayRight
-// TODO: Reverb
-// TODO: Compressor
-// TODO: Overdrive
-// TODO: BitCrusher
-// TODO: Limiter
-// TODO: Bandpass
-// TODO: Chorus
-// TODO: Vibrato
-// https://www.masteringbox.com/learn/audio-effects
+/// Echo the input with the given delay, independently for each channel.
+///
+/// The output is always stereo. Params:
+///
+/// 0. Delay time of the left channel (in samples).
+/// 1. Delay time of the right channel (in samples).
+/// 2. Feedback: how much of the delayed signal is fed back into the delay line.
+///    From -0.99 to 0.99, so that the echo always dies out.
+/// 3. Mix: 0 is only the dry signal, 1 is only the delayed signal.
+/// 4. Damping: how much high frequencies are attenuated on each repeat.
+/// 5. Ping-pong: if 0.5 or above, the echo bounces between the channels.
+///
+/// Changing the delay time glides to the new value instead of jumping,
+/// so it can be safely modulated.
+pub struct Delay {
+    left: DelayLine,
+    right: DelayLine,
+    time_left: f32,
+    time_right: f32,
+    target_left: f32,
+    target_right: f32,
+    feedback: f32,
+    mix: f32,
+    damping: f32,
+    ping_pong: bool,
+    damp_left: f32,
+    damp_right: f32,
+}
+
+impl Delay {
+    /// How fast the delay time glides to the new value on each sample.
+    const GLIDE: f32 = 0.001;
+
+    /// Create a delay with the same delay time for both channels.
+    ///
+    /// The `max_delay` is the longest delay time (in samples) that can be set later.
+    /// It defines how much memory is allocated: 4 bytes per sample per channel.
+    #[must_use]
+    pub fn new(max_delay: Position, time: f32, feedback: f32, mix: f32) -> Self {
+        Self::new_stereo(max_delay, time, time, feedback, mix)
+    }
+
+    /// Create a delay with different delay times for each channel.
+    #[must_use]
+    pub fn new_stereo(
+        max_delay: Position,
+        time_left: f32,
+        time_right: f32,
+        feedback: f32,
+        mix: f32,
+    ) -> Self {
+        let size = max_delay as usize + 2;
+        Self {
+            left: DelayLine::new(size),
+            right: DelayLine::new(size),
+            time_left,
+            time_right,
+            target_left: time_left,
+            target_right: time_right,
+            feedback: feedback.clamp(-0.99, 0.99),
+            mix,
+            damping: 0.,
+            ping_pong: false,
+            damp_left: 0.,
+            damp_right: 0.,
+        }
+    }
+
+    /// Create a delay that bounces the echo between the left and right channels.
+    #[must_use]
+    pub fn new_ping_pong(max_delay: Position, time: f32, feedback: f32, mix: f32) -> Self {
+        let mut res = Self::new(max_delay, time, feedback, mix);
+        res.ping_pong = true;
+        res
+    }
+}
+
+impl Processor for Delay {
+    fn reset(&mut self) {
+        self.left.reset();
+        self.right.reset();
+        self.time_left = self.target_left;
+        self.time_right = self.target_right;
+        self.damp_left = 0.;
+        self.damp_right = 0.;
+    }
+
+    fn set(&mut self, param: u8, val: f32) {
+        match param {
+            0 => self.target_left = val,
+            1 => self.target_right = val,
+            2 => self.feedback = val.clamp(-0.99, 0.99),
+            3 => self.mix = val,
+            4 => self.damping = val.clamp(0., 1.),
+            5 => self.ping_pong = val >= 0.5,
+            _ => {}
+        }
+    }
+
+    fn process_frame(&mut self, f: Frame) -> Option<Frame> {
+        let mut left = f.left.to_array();
+        let mut right = f.right.unwrap_or(f.left).to_array();
+        let dry = 1. - self.mix;
+        for (l, r) in left.iter_mut().zip(right.iter_mut()) {
+            self.time_left += (self.target_left - self.time_left) * Self::GLIDE;
+            self.time_right += (self.target_right - self.time_right) * Self::GLIDE;
+            let wet_left = self.left.read(self.time_left);
+            let wet_right = self.right.read(self.time_right);
+
+            // One-pole low-pass filter in the feedback path.
+            let keep = 1. - self.damping;
+            self.damp_left += (wet_left - self.damp_left) * keep;
+            self.damp_right += (wet_right - self.damp_right) * keep;
+            let fb_left = self.damp_left * self.feedback;
+            let fb_right = self.damp_right * self.feedback;
+
+            if self.ping_pong {
+                self.left.write(f32::midpoint(*l, *r) + fb_right);
+                self.right.write(fb_left);
+            } else {
+                self.left.write(*l + fb_left);
+                self.right.write(*r + fb_right);
+            }
+            *l = F32Ext::mul_add(*l, dry, wet_left * self.mix);
+            *r = F32Ext::mul_add(*r, dry, wet_right * self.mix);
+        }
+        Some(Frame::stereo(Sample::new(left), Sample::new(right)))
+    }
+}
+
+/// A ring buffer of the most recent samples.
+struct DelayLine {
+    buf: Vec<f32>,
+    pos: usize,
+}
+
+impl DelayLine {
+    fn new(size: usize) -> Self {
+        Self {
+            buf: vec![0.; size.max(2)],
+            pos: 0,
+        }
+    }
+
+    fn reset(&mut self) {
+        self.buf.fill(0.);
+        self.pos = 0;
+    }
+
+    fn write(&mut self, s: f32) {
+        self.buf[self.pos] = s;
+        self.pos += 1;
+        if self.pos == self.buf.len() {
+            self.pos = 0;
+        }
+    }
+
+    /// Read the sample written the given number of samples ago.
+    ///
+    /// Fractional delays are linearly interpolated between the two closest samples.
+    #[expect(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn read(&self, delay: f32) -> f32 {
+        let len = self.buf.len();
+        let delay = delay.clamp(1., (len - 1) as f32);
+        let whole = delay as usize;
+        let frac = delay - whole as f32;
+        let a = self.buf[(self.pos + len - whole) % len];
+        let b = self.buf[(self.pos + len - whole - 1) % len];
+        F32Ext::mul_add(b - a, frac, a)
+    }
+
+    /// Read the sample that will be overwritten by the next write.
+    ///
+    /// That's the sample written as many samples ago as the line is long.
+    fn oldest(&self) -> f32 {
+        self.buf[self.pos]
+    }
+}
+
+/// Algorithmic reverberation based on [Freeverb].
+///
+/// To fit into the memory budget of the device, it uses 4 comb filters
+/// (instead of 8 in the original Freeverb) and 4 all-pass filters
+/// for each channel. That's about 55 KB of memory in total.
+///
+/// The input is mixed into mono, the output is always stereo. Params:
+///
+/// 0. Room size: from 0 (small room) to 1 (huge hall).
+/// 1. Damping: how fast high frequencies decay.
+/// 2. Width: stereo separation of the reverberated signal, from 0 (mono) to 1.
+/// 3. Mix: 0 is only the dry signal, 1 is only the reverberated signal.
+///
+/// [Freeverb]: https://ccrma.stanford.edu/~jos/pasp/Freeverb.html
+pub struct Reverb {
+    combs_left: [Comb; 4],
+    combs_right: [Comb; 4],
+    allpasses_left: [Allpass; 4],
+    allpasses_right: [Allpass; 4],
+    width: f32,
+    mix: f32,
+}
+
+impl Reverb {
+    /// Comb filter delays (in samples) for the left channel.
+    const COMBS: [usize; 4] = [1116, 1277, 1422, 1557];
+    /// All-pass filter delays (in samples) for the left channel.
+    const ALLPASSES: [usize; 4] = [556, 441, 341, 225];
+    /// How much longer the delays are for the right channel.
+    const SPREAD: usize = 23;
+    /// Input attenuation to keep the sum of combs from clipping.
+    const GAIN: f32 = 0.03;
+
+    #[must_use]
+    pub fn new(room_size: f32, damping: f32, mix: f32) -> Self {
+        let mut res = Self {
+            combs_left: Self::COMBS.map(Comb::new),
+            combs_right: Self::COMBS.map(|d| Comb::new(d + Self::SPREAD)),
+            allpasses_left: Self::ALLPASSES.map(Allpass::new),
+            allpasses_right: Self::ALLPASSES.map(|d| Allpass::new(d + Self::SPREAD)),
+            width: 1.,
+            mix,
+        };
+        res.set_room_size(room_size);
+        res.set_damping(damping);
+        res
+    }
+
+    fn set_room_size(&mut self, val: f32) {
+        let feedback = F32Ext::mul_add(val.clamp(0., 1.), 0.28, 0.7);
+        for comb in self.combs_left.iter_mut().chain(&mut self.combs_right) {
+            comb.feedback = feedback;
+        }
+    }
+
+    fn set_damping(&mut self, val: f32) {
+        let damping = val.clamp(0., 1.) * 0.4;
+        for comb in self.combs_left.iter_mut().chain(&mut self.combs_right) {
+            comb.damping = damping;
+        }
+    }
+}
+
+impl Processor for Reverb {
+    fn reset(&mut self) {
+        for comb in self.combs_left.iter_mut().chain(&mut self.combs_right) {
+            comb.reset();
+        }
+        for allpass in self
+            .allpasses_left
+            .iter_mut()
+            .chain(&mut self.allpasses_right)
+        {
+            allpass.line.reset();
+        }
+    }
+
+    fn set(&mut self, param: u8, val: f32) {
+        match param {
+            0 => self.set_room_size(val),
+            1 => self.set_damping(val),
+            2 => self.width = val.clamp(0., 1.),
+            3 => self.mix = val,
+            _ => {}
+        }
+    }
+
+    fn process_frame(&mut self, f: Frame) -> Option<Frame> {
+        let mut left = f.left.to_array();
+        let mut right = f.right.unwrap_or(f.left).to_array();
+        let wet1 = self.mix * F32Ext::mul_add(self.width, 0.5, 0.5);
+        let wet2 = self.mix * (1. - self.width) * 0.5;
+        let dry = 1. - self.mix;
+        for (l, r) in left.iter_mut().zip(right.iter_mut()) {
+            let input = (*l + *r) * Self::GAIN;
+            let mut out_left = 0.;
+            for comb in &mut self.combs_left {
+                out_left += comb.process(input);
+            }
+            let mut out_right = 0.;
+            for comb in &mut self.combs_right {
+                out_right += comb.process(input);
+            }
+            for allpass in &mut self.allpasses_left {
+                out_left = allpass.process(out_left);
+            }
+            for allpass in &mut self.allpasses_right {
+                out_right = allpass.process(out_right);
+            }
+            let new_left = F32Ext::mul_add(out_right, wet2, out_left * wet1);
+            let new_right = F32Ext::mul_add(out_left, wet2, out_right * wet1);
+            *l = F32Ext::mul_add(*l, dry, new_left);
+            *r = F32Ext::mul_add(*r, dry, new_right);
+        }
+        Some(Frame::stereo(Sample::new(left), Sample::new(right)))
+    }
+}
+
+/// Lowpass-feedback comb filter, a building block of [`Reverb`].
+struct Comb {
+    line: DelayLine,
+    feedback: f32,
+    damping: f32,
+    store: f32,
+}
+
+impl Comb {
+    fn new(delay: usize) -> Self {
+        Self {
+            line: DelayLine::new(delay),
+            feedback: 0.,
+            damping: 0.,
+            store: 0.,
+        }
+    }
+
+    fn reset(&mut self) {
+        self.line.reset();
+        self.store = 0.;
+    }
+
+    fn process(&mut self, input: f32) -> f32 {
+        let output = self.line.oldest();
+        self.store = F32Ext::mul_add(self.store - output, self.damping, output);
+        self.line
+            .write(F32Ext::mul_add(self.store, self.feedback, input));
+        output
+    }
+}
+
+/// Schroeder all-pass filter, a building block of [`Reverb`].
+struct Allpass {
+    line: DelayLine,
+}
+
+impl Allpass {
+    const FEEDBACK: f32 = 0.5;
+
+    fn new(delay: usize) -> Self {
+        Self {
+            line: DelayLine::new(delay),
+        }
+    }
+
+    fn process(&mut self, input: f32) -> f32 {
+        let delayed = self.line.oldest();
+        self.line
+            .write(F32Ext::mul_add(delayed, Self::FEEDBACK, input));
+        delayed - input
+    }
+}
+
+/// Feed-forward dynamic range compressor.
+///
+/// The gain reduction is calculated from the louder of the two channels
+/// and applied to both, so the stereo image doesn't shift. Params:
+///
+/// 0. Threshold (in dB): the level above which the signal is compressed.
+/// 1. Ratio: how many dB of input above the threshold produce 1 dB of output.
+/// 2. Knee (in dB): the width of the soft transition around the threshold.
+/// 3. Attack (in samples): how fast the compression kicks in.
+/// 4. Release (in samples): how fast the compression stops.
+/// 5. Makeup gain (in dB): applied to the output after compression.
+pub struct Compressor {
+    threshold: f32,
+    ratio: f32,
+    knee: f32,
+    attack: f32,
+    release: f32,
+    makeup: f32,
+    /// The current gain reduction (in dB, zero or negative).
+    reduction: f32,
+}
+
+impl Compressor {
+    #[must_use]
+    pub fn new(threshold: f32, ratio: f32, attack: f32, release: f32) -> Self {
+        Self {
+            threshold,
+            ratio: ratio.max(1.),
+            knee: 0.,
+            attack: time_coef(attack),
+            release: time_coef(release),
+            makeup: 0.,
+            reduction: 0.,
+        }
+    }
+
+    /// Calculate the desired gain reduction (in dB) for the given input level (in dB).
+    fn reduction_for(&self, level: f32) -> f32 {
+        let over = level - self.threshold;
+        let slope = 1. / self.ratio - 1.;
+        if 2. * over <= -self.knee {
+            0.
+        } else if 2. * over.abs() < self.knee {
+            let x = over + self.knee / 2.;
+            slope * x * x / (2. * self.knee)
+        } else {
+            slope * over
+        }
+    }
+}
+
+impl Processor for Compressor {
+    fn reset(&mut self) {
+        self.reduction = 0.;
+    }
+
+    fn set(&mut self, param: u8, val: f32) {
+        match param {
+            0 => self.threshold = val,
+            1 => self.ratio = val.max(1.),
+            2 => self.knee = val.max(0.),
+            3 => self.attack = time_coef(val),
+            4 => self.release = time_coef(val),
+            5 => self.makeup = val,
+            _ => {}
+        }
+    }
+
+    fn process_frame(&mut self, f: Frame) -> Option<Frame> {
+        let mut left = f.left.to_array();
+        let mut right = f.right.map(Sample::to_array);
+        for (i, l) in left.iter_mut().enumerate() {
+            let mut peak = l.abs();
+            if let Some(right) = &right {
+                peak = peak.max(right[i].abs());
+            }
+            let target = self.reduction_for(amp_to_db(peak));
+            let coef = if target < self.reduction {
+                self.attack
+            } else {
+                self.release
+            };
+            self.reduction = F32Ext::mul_add(self.reduction - target, coef, target);
+            let gain = db_to_amp(self.reduction + self.makeup);
+            *l *= gain;
+            if let Some(right) = &mut right {
+                right[i] *= gain;
+            }
+        }
+        let left = Sample::new(left);
+        let right = right.map(Sample::new);
+        Some(Frame { left, right })
+    }
+}
+
+/// Convert a time constant (in samples) into a one-pole smoothing coefficient.
+///
+/// The smoothed value covers about 63% of the distance to the target
+/// in the given time.
+fn time_coef(time: f32) -> f32 {
+    if time <= 0. {
+        return 0.;
+    }
+    F32Ext::exp(-1. / time)
+}
+
+/// Convert decibels into a linear amplitude.
+fn db_to_amp(db: f32) -> f32 {
+    F32Ext::powf(10., db / 20.)
+}
+
+/// Convert a linear amplitude into decibels.
+fn amp_to_db(amp: f32) -> f32 {
+    20. * F32Ext::log10(amp.max(1e-6))
+}
+
+/// The transfer function used by [`Overdrive`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Waveshape {
+    /// Smooth saturation following the hyperbolic tangent.
+    Soft,
+    /// Cut everything above the full scale.
+    Hard,
+    /// Fold the signal going above the full scale back down.
+    Foldback,
+    /// Asymmetric saturation, like an overdriven vacuum tube.
+    Tube,
+}
+
+impl Waveshape {
+    /// The bias applied before [`Waveshape::Tube`] saturation.
+    const TUBE_BIAS: f32 = 0.3;
+
+    fn from_param(val: f32) -> Self {
+        if val < 0.5 {
+            Self::Soft
+        } else if val < 1.5 {
+            Self::Hard
+        } else if val < 2.5 {
+            Self::Foldback
+        } else {
+            Self::Tube
+        }
+    }
+
+    fn apply(self, s: Sample) -> Sample {
+        match self {
+            Self::Soft => s.tanh(),
+            Self::Hard => s.fast_clamp(Sample::splat(-1.), Sample::ONE),
+            Self::Foldback => {
+                let t = (s - 1.).rem_euclid(Sample::splat(4.));
+                (t - 2.).abs() - 1.
+            }
+            Self::Tube => {
+                let bias = Sample::splat(Self::TUBE_BIAS);
+                (s + bias).tanh() - bias.tanh()
+            }
+        }
+    }
+}
+
+/// Distort the signal by amplifying and then saturating it.
+///
+/// Params:
+///
+/// 0. Drive: the gain applied before the saturation.
+/// 1. Tone: brightness of the output, from 0 (dark) to 1 (no filtering).
+/// 2. Output gain: applied after the saturation.
+/// 3. Shape: 0 is [`Waveshape::Soft`], 1 is [`Waveshape::Hard`],
+///    2 is [`Waveshape::Foldback`], and 3 is [`Waveshape::Tube`].
+pub struct Overdrive {
+    shape: Waveshape,
+    drive: f32,
+    output: f32,
+    tone: f32,
+    oversample: bool,
+    left: OverdriveState,
+    right: OverdriveState,
+}
+
+impl Overdrive {
+    #[must_use]
+    pub const fn new(shape: Waveshape, drive: f32) -> Self {
+        Self {
+            shape,
+            drive,
+            output: 1.,
+            tone: 1.,
+            oversample: false,
+            left: OverdriveState::new(),
+            right: OverdriveState::new(),
+        }
+    }
+
+    /// Like [`Overdrive::new`] but runs the saturation at twice the sample rate.
+    ///
+    /// Saturation produces high harmonics that fold back into the audible range
+    /// as inharmonic noise ("aliasing"). Oversampling reduces it
+    /// at the cost of twice as much CPU time.
+    #[must_use]
+    pub const fn new_oversampled(shape: Waveshape, drive: f32) -> Self {
+        let mut res = Self::new(shape, drive);
+        res.oversample = true;
+        res
+    }
+}
+
+impl Processor for Overdrive {
+    fn reset(&mut self) {
+        self.left = OverdriveState::new();
+        self.right = OverdriveState::new();
+    }
+
+    fn set(&mut self, param: u8, val: f32) {
+        match param {
+            0 => self.drive = val,
+            1 => {
+                // Map tone onto the cutoff frequency from 200 Hz to 20 kHz.
+                // Full brightness bypasses the filter.
+                self.tone = if val >= 1. {
+                    1.
+                } else {
+                    let freq = 200. * F32Ext::powf(100., val.max(0.));
+                    let w = core::f32::consts::TAU * freq * SAMPLE_DURATION;
+                    1. - F32Ext::exp(-w)
+                };
+            }
+            2 => self.output = val,
+            3 => self.shape = Waveshape::from_param(val),
+            _ => {}
+        }
+    }
+
+    fn process_frame(&mut self, f: Frame) -> Option<Frame> {
+        let (shape, oversample, tone) = (self.shape, self.oversample, self.tone);
+        let left = f.left * self.drive;
+        let left = self.left.process(left, shape, oversample, tone) * self.output;
+        // Mono input is processed for both channels, so that the right channel
+        // has no stale state when the input switches to stereo.
+        let right = f.right.unwrap_or(f.left) * self.drive;
+        let right = self.right.process(right, shape, oversample, tone) * self.output;
+        let right = f.right.map(|_| right);
+        Some(Frame { left, right })
+    }
+}
+
+/// Per-channel state of [`Overdrive`].
+struct OverdriveState {
+    /// The last input sample of the previous frame.
+    prev: f32,
+    /// The last saturated sample of the previous frame.
+    prev_shaped: f32,
+    /// The state of the tone filter.
+    tone: f32,
+}
+
+impl OverdriveState {
+    const fn new() -> Self {
+        Self {
+            prev: 0.,
+            prev_shaped: 0.,
+            tone: 0.,
+        }
+    }
+
+    fn process(&mut self, s: Sample, shape: Waveshape, oversample: bool, tone: f32) -> Sample {
+        let shaped = shape.apply(s);
+        let s = if oversample {
+            // Upsample by inserting linearly interpolated samples in between,
+            // saturate both, and downsample with a half-band triangular filter.
+            // Each sample is paired with the one before it, so all samples
+            // are shifted one position later, putting the given value first.
+            let shift_in = |first: f32, s: Sample| {
+                let s = s.to_array();
+                Sample::new([first, s[0], s[1], s[2], s[3], s[4], s[5], s[6]])
+            };
+            let prev = shift_in(self.prev, s);
+            let mid = shape.apply((prev + s) * 0.5);
+            let prev_shaped = shift_in(self.prev_shaped, shaped);
+            self.prev = s.as_array()[7];
+            self.prev_shaped = shaped.as_array()[7];
+            (prev_shaped + shaped) * 0.25 + mid * 0.5
+        } else {
+            shaped
+        };
+        if tone >= 1. {
+            return s;
+        }
+        let mut samples = s.to_array();
+        for sample in &mut samples {
+            self.tone += (*sample - self.tone) * tone;
+            *sample = self.tone;
+        }
+        Sample::new(samples)
+    }
+}
+
+/// Brickwall limiter with a look-ahead.
+///
+/// The input is delayed by the look-ahead time, which lets the limiter
+/// reduce the gain smoothly before a peak arrives instead of clipping it.
+/// The gain reduction is calculated from the louder of the two channels
+/// and applied to both. Params:
+///
+/// 0. Ceiling (in dB): the output never goes above this level.
+/// 1. Release (in samples): how fast the gain recovers after a peak.
+///
+/// Can be applied to the whole mix using [`Manager::set_limiter`].
+pub struct Limiter {
+    ceiling: f32,
+    release: f32,
+    lookahead: u32,
+    left: DelayLine,
+    right: DelayLine,
+    gain: f32,
+    target: f32,
+    step: f32,
+    hold: u32,
+    /// How many samples of the delayed input are still waiting to be emitted.
+    tail: u32,
+    /// How many samples of stereo input are still waiting to be emitted.
+    stereo_tail: u32,
+}
+
+impl Limiter {
+    /// Create a new limiter.
+    ///
+    /// The `lookahead` is the output latency (in samples).
+    /// A few milliseconds (100-250 samples) is usually enough.
+    #[must_use]
+    pub fn new(ceiling: f32, lookahead: Position, release: f32) -> Self {
+        let lookahead = lookahead.max(1);
+        Self {
+            ceiling: db_to_amp(ceiling),
+            release: time_coef(release),
+            lookahead,
+            left: DelayLine::new(lookahead as usize),
+            right: DelayLine::new(lookahead as usize),
+            gain: 1.,
+            target: 1.,
+            step: 0.,
+            hold: 0,
+            tail: 0,
+            stereo_tail: 0,
+        }
+    }
+
+    /// Limit the given frame or, if there is no input, emit what's left in the buffer.
+    pub(crate) fn next(&mut self, f: Option<Frame>) -> Option<Frame> {
+        if let Some(f) = f {
+            self.tail = self.lookahead;
+            return self.process_frame(f);
+        }
+        if self.tail == 0 {
+            return None;
+        }
+        self.tail = self.tail.saturating_sub(8);
+        self.process_frame(Frame::zero())
+    }
+
+    /// Calculate the gain for the next sample given the peak of the incoming sample.
+    fn next_gain(&mut self, peak: f32) -> f32 {
+        let required = if peak > self.ceiling {
+            self.ceiling / peak
+        } else {
+            1.
+        };
+        if required < self.target {
+            // Ramp down so that the gain reaches the required level
+            // exactly when the peak leaves the look-ahead buffer.
+            let step = (self.gain - required) / self.lookahead as f32;
+            self.step = self.step.max(step);
+            self.target = required;
+            self.hold = self.lookahead;
+        } else if required <= self.gain {
+            self.hold = self.lookahead;
+        }
+
+        if self.gain > self.target {
+            self.gain = (self.gain - self.step).max(self.target);
+        } else if self.hold > 0 {
+            self.hold -= 1;
+        } else {
+            self.step = 0.;
+            self.gain = F32Ext::mul_add(self.gain - 1., self.release, 1.);
+            self.target = self.gain;
+        }
+        self.gain
+    }
+}
+
+impl Processor for Limiter {
+    fn reset(&mut self) {
+        self.left.reset();
+        self.right.reset();
+        self.gain = 1.;
+        self.target = 1.;
+        self.step = 0.;
+        self.hold = 0;
+        self.tail = 0;
+        self.stereo_tail = 0;
+    }
+
+    fn set(&mut self, param: u8, val: f32) {
+        match param {
+            0 => self.ceiling = db_to_amp(val),
+            1 => self.release = time_coef(val),
+            _ => {}
+        }
+    }
+
+    fn process_children(&mut self, cn: &mut [Node]) -> Option<Frame> {
+        let f = Mix::new().process_children(cn);
+        self.next(f)
+    }
+
+    fn process_frame(&mut self, f: Frame) -> Option<Frame> {
+        // Both delay lines are always written, so that the delayed right channel
+        // is correct when the input switches between mono and stereo.
+        // The output stays stereo until all stereo input is emitted.
+        if f.right.is_some() {
+            self.stereo_tail = self.lookahead;
+        }
+        let stereo = self.stereo_tail > 0;
+        if f.right.is_none() {
+            self.stereo_tail = self.stereo_tail.saturating_sub(8);
+        }
+
+        let mut left = f.left.to_array();
+        let mut right = f.right.unwrap_or(f.left).to_array();
+        let ceiling = self.ceiling;
+        for (l, r) in left.iter_mut().zip(right.iter_mut()) {
+            let gain = self.next_gain(l.abs().max(r.abs()));
+            let delayed = self.left.oldest();
+            self.left.write(*l);
+            *l = (delayed * gain).clamp(-ceiling, ceiling);
+            let delayed = self.right.oldest();
+            self.right.write(*r);
+            *r = (delayed * gain).clamp(-ceiling, ceiling);
+        }
+        let left = Sample::new(left);
+        if stereo {
+            Some(Frame::stereo(left, Sample::new(right)))
+        } else {
+            Some(Frame::mono(left))
+        }
+    }
+}
+
+/// Reduce the bit depth and the sample rate for the lo-fi retro sound.
+///
+/// Params:
+///
+/// 0. Bits: the resolution of each sample, from 1 to 24. Can be fractional.
+/// 1. Downsample factor: how many samples in a row are replaced by the first one.
+///    1 means no downsampling, 4 turns 44.1 kHz into 11 kHz. Can be fractional.
+pub struct BitCrusher {
+    /// The number of quantization steps in each direction from zero.
+    levels: f32,
+    factor: f32,
+    phase: f32,
+    hold_left: f32,
+    hold_right: f32,
+}
+
+impl BitCrusher {
+    #[must_use]
+    pub fn new(bits: f32, factor: f32) -> Self {
+        Self {
+            levels: bits_to_levels(bits),
+            factor: factor.max(1.),
+            phase: 0.,
+            hold_left: 0.,
+            hold_right: 0.,
+        }
+    }
+}
+
+fn bits_to_levels(bits: f32) -> f32 {
+    F32Ext::powf(2., bits.clamp(1., 24.) - 1.)
+}
+
+impl Processor for BitCrusher {
+    fn reset(&mut self) {
+        self.phase = 0.;
+        self.hold_left = 0.;
+        self.hold_right = 0.;
+    }
+
+    fn set(&mut self, param: u8, val: f32) {
+        match param {
+            0 => self.levels = bits_to_levels(val),
+            1 => self.factor = val.max(1.),
+            _ => {}
+        }
+    }
+
+    fn process_frame(&mut self, f: Frame) -> Option<Frame> {
+        let mut left = f.left.to_array();
+        let mut right = f.right.map(Sample::to_array);
+        for (i, l) in left.iter_mut().enumerate() {
+            // Sample-and-hold: take a new sample every `factor` samples.
+            self.phase -= 1.;
+            if self.phase <= 0. {
+                self.phase += self.factor;
+                self.hold_left = *l;
+                if let Some(right) = &right {
+                    self.hold_right = right[i];
+                }
+            }
+            *l = self.hold_left;
+            if let Some(right) = &mut right {
+                right[i] = self.hold_right;
+            }
+        }
+        let levels = self.levels;
+        let crush = |s: [f32; 8]| (Sample::new(s) * levels).round() / levels;
+        let left = crush(left);
+        let right = right.map(crush);
+        Some(Frame { left, right })
+    }
+}
+
+/// The frequency response of the [`Biquad`] filter.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FilterType {
+    /// Pass frequencies below the cutoff.
+    LowPass,
+    /// Pass frequencies above the cutoff.
+    HighPass,
+    /// Pass frequencies around the center, with 0 dB peak gain.
+    BandPass,
+    /// Cut frequencies around the center.
+    Notch,
+    /// Pass all frequencies, shift the phase around the center.
+    AllPass,
+    /// Boost or cut frequencies around the center by the given gain.
+    Peak,
+    /// Boost or cut frequencies below the cutoff by the given gain.
+    LowShelf,
+    /// Boost or cut frequencies above the cutoff by the given gain.
+    HighShelf,
+}
+
+/// Second-order IIR filter based on [Audio EQ Cookbook] by Robert Bristow-Johnson.
+///
+/// Params:
+///
+/// 0. Cutoff or center frequency (in Hz).
+/// 1. Q factor: resonance or, for band filters, the inverse of the bandwidth.
+/// 2. Gain (in dB): used only by [`FilterType::Peak`] and the shelf filters.
+///
+/// Changed params glide to the new value over a few milliseconds,
+/// so they can be safely modulated.
+///
+/// [Audio EQ Cookbook]: https://www.w3.org/TR/audio-eq-cookbook/
+pub struct Biquad {
+    kind: FilterType,
+    freq: f32,
+    q: f32,
+    gain: f32,
+    target_freq: f32,
+    target_q: f32,
+    target_gain: f32,
+    coefs: BiquadCoefs,
+    left: BiquadState,
+    right: BiquadState,
+}
+
+impl Biquad {
+    /// How much closer the params get to the new value on each frame.
+    const GLIDE: f32 = 0.3;
+
+    #[must_use]
+    pub fn new(kind: FilterType, freq: f32, q: f32, gain: f32) -> Self {
+        Self {
+            kind,
+            freq,
+            q,
+            gain,
+            target_freq: freq,
+            target_q: q,
+            target_gain: gain,
+            coefs: BiquadCoefs::new(kind, freq, q, gain),
+            left: BiquadState::default(),
+            right: BiquadState::default(),
+        }
+    }
+
+    /// Move the params closer to the target values.
+    ///
+    /// Returns true if the params have changed.
+    fn glide(&mut self) -> bool {
+        let mut changed = false;
+        for (val, target, eps) in [
+            (&mut self.freq, self.target_freq, 0.01),
+            (&mut self.q, self.target_q, 0.0001),
+            (&mut self.gain, self.target_gain, 0.001),
+        ] {
+            let diff = target - *val;
+            if diff.abs() <= eps {
+                changed |= diff != 0.;
+                *val = target;
+            } else {
+                changed = true;
+                *val = F32Ext::mul_add(diff, Self::GLIDE, *val);
+            }
+        }
+        changed
+    }
+}
+
+impl Processor for Biquad {
+    fn reset(&mut self) {
+        self.left = BiquadState::default();
+        self.right = BiquadState::default();
+    }
+
+    fn set(&mut self, param: u8, val: f32) {
+        match param {
+            0 => self.target_freq = val,
+            1 => self.target_q = val,
+            2 => self.target_gain = val,
+            _ => {}
+        }
+    }
+
+    fn process_frame(&mut self, f: Frame) -> Option<Frame> {
+        let old = self.coefs;
+        if self.glide() {
+            self.coefs = BiquadCoefs::new(self.kind, self.freq, self.q, self.gain);
+        }
+        let new = self.coefs;
+        let left = self.left.process(f.left, &old, &new);
+        // Mono input is filtered for both channels, so that the right channel
+        // doesn't ring with stale state when the input switches to stereo.
+        let right = self.right.process(f.right.unwrap_or(f.left), &old, &new);
+        let right = f.right.map(|_| right);
+        Some(Frame { left, right })
+    }
+}
+
+#[derive(Clone, Copy)]
+struct BiquadCoefs {
+    b0: f32,
+    b1: f32,
+    b2: f32,
+    a1: f32,
+    a2: f32,
+}
+
+impl BiquadCoefs {
+    fn new(kind: FilterType, freq: f32, q: f32, gain: f32) -> Self {
+        let nyquist = SAMPLE_RATE as f32 / 2.;
+        let freq = freq.clamp(10., nyquist * 0.99);
+        let w0 = core::f32::consts::TAU * freq * SAMPLE_DURATION;
+        let (sin_w0, cos_w0) = F32Ext::sin_cos(w0);
+        let alpha = sin_w0 / (2. * q.max(0.01));
+        let amp = F32Ext::powf(10., gain / 40.);
+
+        let (b0, b1, b2, a0, a1, a2) = match kind {
+            FilterType::LowPass => {
+                let b1 = 1. - cos_w0;
+                (b1 / 2., b1, b1 / 2., 1. + alpha, -2. * cos_w0, 1. - alpha)
+            }
+            FilterType::HighPass => {
+                let b1 = -1. - cos_w0;
+                (-b1 / 2., b1, -b1 / 2., 1. + alpha, -2. * cos_w0, 1. - alpha)
+            }
+            FilterType::BandPass => (alpha, 0., -alpha, 1. + alpha, -2. * cos_w0, 1. - alpha),
+            FilterType::Notch => (1., -2. * cos_w0, 1., 1. + alpha, -2. * cos_w0, 1. - alpha),
+            FilterType::AllPass => {
+                let b0 = 1. - alpha;
+                let b2 = 1. + alpha;
+                (b0, -2. * cos_w0, b2, b2, -2. * cos_w0, b0)
+            }
+            FilterType::Peak => (
+                F32Ext::mul_add(alpha, amp, 1.),
+                -2. * cos_w0,
+                F32Ext::mul_add(-alpha, amp, 1.),
+                1. + alpha / amp,
+                -2. * cos_w0,
+                1. - alpha / amp,
+            ),
+            FilterType::LowShelf => {
+                let shelf = 2. * F32Ext::sqrt(amp) * alpha;
+                let (plus, minus) = (amp + 1., amp - 1.);
+                let (plus_cos, minus_cos) = (plus * cos_w0, minus * cos_w0);
+                (
+                    amp * (plus - minus_cos + shelf),
+                    2. * amp * (minus - plus_cos),
+                    amp * (plus - minus_cos - shelf),
+                    plus + minus_cos + shelf,
+                    -2. * (minus + plus_cos),
+                    plus + minus_cos - shelf,
+                )
+            }
+            FilterType::HighShelf => {
+                let shelf = 2. * F32Ext::sqrt(amp) * alpha;
+                let (plus, minus) = (amp + 1., amp - 1.);
+                let (plus_cos, minus_cos) = (plus * cos_w0, minus * cos_w0);
+                (
+                    amp * (plus + minus_cos + shelf),
+                    -2. * amp * (minus + plus_cos),
+                    amp * (plus + minus_cos - shelf),
+                    plus - minus_cos + shelf,
+                    2. * (minus - plus_cos),
+                    plus - minus_cos - shelf,
+                )
+            }
+        };
+        Self {
+            b0: b0 / a0,
+            b1: b1 / a0,
+            b2: b2 / a0,
+            a1: a1 / a0,
+            a2: a2 / a0,
+        }
+    }
+
+    /// Linearly interpolate between two sets of coefficients.
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        let lerp = |a: f32, b: f32| F32Ext::mul_add(b - a, t, a);
+        Self {
+            b0: lerp(self.b0, other.b0),
+            b1: lerp(self.b1, other.b1),
+            b2: lerp(self.b2, other.b2),
+            a1: lerp(self.a1, other.a1),
+            a2: lerp(self.a2, other.a2),
+        }
+    }
+}
+
+/// Per-channel state of [`Biquad`] (transposed direct form II).
+#[derive(Default)]
+struct BiquadState {
+    z1: f32,
+    z2: f32,
+}
+
+impl BiquadState {
+    /// Filter the given samples, gradually moving from the old coefficients to the new ones.
+    fn process(&mut self, s: Sample, old: &BiquadCoefs, new: &BiquadCoefs) -> Sample {
+        let mut samples = s.to_array();
+        for (i, x) in samples.iter_mut().enumerate() {
+            let c = old.lerp(new, (i + 1) as f32 / 8.);
+            let y = F32Ext::mul_add(c.b0, *x, self.z1);
+            self.z1 = F32Ext::mul_add(c.b1, *x, F32Ext::mul_add(-c.a1, y, self.z2));
+            self.z2 = F32Ext::mul_add(c.b2, *x, -c.a2 * y);
+            *x = y;
+        }
+        Sample::new(samples)
+    }
+}
+
+/// The output of [`StateVariableFilter`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SvfMode {
+    LowPass,
+    HighPass,
+    /// Band-pass with 0 dB peak gain.
+    BandPass,
+    Notch,
+}
+
+impl SvfMode {
+    fn from_param(val: f32) -> Self {
+        if val < 0.5 {
+            Self::LowPass
+        } else if val < 1.5 {
+            Self::HighPass
+        } else if val < 2.5 {
+            Self::BandPass
+        } else {
+            Self::Notch
+        }
+    }
+}
+
+/// Resonant [state variable filter] using the topology-preserving transform.
+///
+/// Unlike [`Biquad`], it stays stable and doesn't click when the cutoff
+/// and the resonance are modulated on every frame, which makes it
+/// the filter of choice for the classic synth filter sweeps. Params:
+///
+/// 0. Cutoff frequency (in Hz).
+/// 1. Resonance (Q factor). 0.707 is no resonance, higher values make
+///    the filter ring around the cutoff.
+/// 2. Mode: 0 is [`SvfMode::LowPass`], 1 is [`SvfMode::HighPass`],
+///    2 is [`SvfMode::BandPass`], and 3 is [`SvfMode::Notch`].
+///
+/// [state variable filter]: https://cytomic.com/files/dsp/SvfLinearTrapOptimised2.pdf
+pub struct StateVariableFilter {
+    mode: SvfMode,
+    /// The cutoff frequency pre-warped for the sample rate.
+    g: f32,
+    /// The damping, inverse of the resonance.
+    k: f32,
+    /// The value of `g` on the previous frame, used to interpolate changes.
+    prev_g: f32,
+    left: SvfState,
+    right: SvfState,
+}
+
+impl StateVariableFilter {
+    #[must_use]
+    pub fn new(mode: SvfMode, freq: f32, q: f32) -> Self {
+        let g = svf_g(freq);
+        Self {
+            mode,
+            g,
+            k: svf_k(q),
+            prev_g: g,
+            left: SvfState::default(),
+            right: SvfState::default(),
+        }
+    }
+}
+
+fn svf_g(freq: f32) -> f32 {
+    let nyquist = SAMPLE_RATE as f32 / 2.;
+    let freq = freq.clamp(10., nyquist * 0.99);
+    F32Ext::tan(core::f32::consts::PI * freq * SAMPLE_DURATION)
+}
+
+fn svf_k(q: f32) -> f32 {
+    1. / q.max(0.01)
+}
+
+impl Processor for StateVariableFilter {
+    fn reset(&mut self) {
+        self.left = SvfState::default();
+        self.right = SvfState::default();
+        self.prev_g = self.g;
+    }
+
+    fn set(&mut self, param: u8, val: f32) {
+        match param {
+            0 => self.g = svf_g(val),
+            1 => self.k = svf_k(val),
+            2 => self.mode = SvfMode::from_param(val),
+            _ => {}
+        }
+    }
+
+    fn process_frame(&mut self, f: Frame) -> Option<Frame> {
+        let (g0, g1, k, mode) = (self.prev_g, self.g, self.k, self.mode);
+        self.prev_g = self.g;
+        let left = self.left.process(f.left, g0, g1, k, mode);
+        // Mono input is filtered for both channels, so that the right channel
+        // doesn't ring with stale state when the input switches to stereo.
+        let right = self
+            .right
+            .process(f.right.unwrap_or(f.left), g0, g1, k, mode);
+        let right = f.right.map(|_| right);
+        Some(Frame { left, right })
+    }
+}
+
+/// Per-channel state of [`StateVariableFilter`]: the two integrators.
+#[derive(Default)]
+struct SvfState {
+    ic1: f32,
+    ic2: f32,
+}
+
+impl SvfState {
+    /// Filter the given samples, linearly moving the cutoff from `g0` to `g1`.
+    fn process(&mut self, s: Sample, g0: f32, g1: f32, k: f32, mode: SvfMode) -> Sample {
+        let mut samples = s.to_array();
+        for (i, x) in samples.iter_mut().enumerate() {
+            let g = F32Ext::mul_add(g1 - g0, (i + 1) as f32 / 8., g0);
+            let a1 = 1. / F32Ext::mul_add(g, g + k, 1.);
+            let a2 = g * a1;
+            let a3 = g * a2;
+            let v3 = *x - self.ic2;
+            let v1 = F32Ext::mul_add(a1, self.ic1, a2 * v3);
+            let v2 = self.ic2 + F32Ext::mul_add(a2, self.ic1, a3 * v3);
+            self.ic1 = F32Ext::mul_add(2., v1, -self.ic1);
+            self.ic2 = F32Ext::mul_add(2., v2, -self.ic2);
+            let high = F32Ext::mul_add(-k, v1, *x - v2);
+            *x = match mode {
+                SvfMode::LowPass => v2,
+                SvfMode::HighPass => high,
+                SvfMode::BandPass => k * v1,
+                SvfMode::Notch => v2 + high,
+            };
+        }
+        Sample::new(samples)
+    }
+}
+
+/// Mix the input with its copy delayed by a periodically changing time.
+///
+/// With longer delays and no feedback, it's a chorus: the effect of many
+/// instruments playing in unison. With very short delays and feedback,
+/// it's a flanger: the "jet plane" sweeping comb filter.
+///
+/// The delay time of each channel is modulated by its own sine LFO,
+/// with a phase offset between the channels that turns mono input
+/// into wide stereo output. Params:
+///
+/// 0. LFO rate (in Hz).
+/// 1. Depth (in samples): how far the delay time swings from the base delay.
+/// 2. Base delay (in samples).
+/// 3. Feedback: how much of the delayed signal is fed back into the delay line.
+///    Can be negative.
+/// 4. Mix: 0 is only the dry signal, 1 is only the delayed signal.
+/// 5. Phase offset of the right channel LFO, from 0 to 1 (a full period).
+pub struct Chorus {
+    left: DelayLine,
+    right: DelayLine,
+    /// The LFO phase advance on each frame.
+    step: f32,
+    phase: f32,
+    offset: f32,
+    depth: f32,
+    delay: f32,
+    feedback: f32,
+    mix: f32,
+    /// The delay times at the end of the previous frame.
+    prev: (f32, f32),
+}
+
+impl Chorus {
+    /// The longest supported delay time (in samples), including depth.
+    const MAX_DELAY: usize = 2048;
+
+    /// Create a chorus with the base delay of about 20 ms.
+    #[must_use]
+    pub fn new(rate: f32, depth: f32, mix: f32) -> Self {
+        Self::new_inner(rate, depth, 880., 0., mix)
+    }
+
+    /// Create a flanger with the base delay of about 2 ms.
+    #[must_use]
+    pub fn new_flanger(rate: f32, depth: f32, feedback: f32, mix: f32) -> Self {
+        Self::new_inner(rate, depth, 88., feedback, mix)
+    }
+
+    fn new_inner(rate: f32, depth: f32, delay: f32, feedback: f32, mix: f32) -> Self {
+        let size = Self::MAX_DELAY + 2;
+        let mut res = Self {
+            left: DelayLine::new(size),
+            right: DelayLine::new(size),
+            step: rate * 8. * SAMPLE_DURATION,
+            phase: 0.,
+            offset: 0.25,
+            depth,
+            delay,
+            feedback,
+            mix,
+            prev: (0., 0.),
+        };
+        res.prev = res.delays();
+        res
+    }
+
+    /// Calculate the current delay times for both channels.
+    fn delays(&self) -> (f32, f32) {
+        let time = |phase: f32| {
+            let lfo = F32Ext::sin(core::f32::consts::TAU * phase);
+            F32Ext::mul_add(lfo, self.depth, self.delay).clamp(1., Self::MAX_DELAY as f32)
+        };
+        let right_phase = F32Ext::fract(self.phase + self.offset);
+        (time(self.phase), time(right_phase))
+    }
+}
+
+impl Processor for Chorus {
+    fn reset(&mut self) {
+        self.left.reset();
+        self.right.reset();
+        self.phase = 0.;
+        self.prev = self.delays();
+    }
+
+    fn set(&mut self, param: u8, val: f32) {
+        match param {
+            0 => self.step = val * 8. * SAMPLE_DURATION,
+            1 => self.depth = val,
+            2 => self.delay = val,
+            3 => self.feedback = val.clamp(-0.99, 0.99),
+            4 => self.mix = val,
+            5 => self.offset = val,
+            _ => {}
+        }
+    }
+
+    fn process_frame(&mut self, f: Frame) -> Option<Frame> {
+        self.phase = F32Ext::fract(self.phase + self.step);
+        let (start_left, start_right) = self.prev;
+        let (end_left, end_right) = self.delays();
+        self.prev = (end_left, end_right);
+
+        let mut left = f.left.to_array();
+        let mut right = f.right.unwrap_or(f.left).to_array();
+        let dry = 1. - self.mix;
+        for (i, (l, r)) in left.iter_mut().zip(right.iter_mut()).enumerate() {
+            let t = (i + 1) as f32 / 8.;
+            let wet_left = self
+                .left
+                .read(F32Ext::mul_add(end_left - start_left, t, start_left));
+            let wet_right =
+                self.right
+                    .read(F32Ext::mul_add(end_right - start_right, t, start_right));
+            self.left
+                .write(F32Ext::mul_add(wet_left, self.feedback, *l));
+            self.right
+                .write(F32Ext::mul_add(wet_right, self.feedback, *r));
+            *l = F32Ext::mul_add(*l, dry, wet_left * self.mix);
+            *r = F32Ext::mul_add(*r, dry, wet_right * self.mix);
+        }
+        Some(Frame::stereo(Sample::new(left), Sample::new(right)))
+    }
+}
+
+/// Periodically change the pitch by running the input through a delay line
+/// with an LFO-modulated delay time.
+///
+/// Unlike modulating the frequency of an oscillator, it works for any input,
+/// including [`Pcm`] audio. Params:
+///
+/// 0. LFO rate (in Hz).
+/// 1. Depth (in samples): how far the delay time swings. The pitch deviation
+///    is proportional to both rate and depth.
+pub struct Vibrato {
+    left: DelayLine,
+    right: DelayLine,
+    /// The LFO phase advance on each frame.
+    step: f32,
+    phase: f32,
+    depth: f32,
+    /// The delay time at the end of the previous frame.
+    prev: f32,
+    /// How many samples of the output are still affected by stereo input.
+    stereo_tail: usize,
+}
+
+impl Vibrato {
+    /// The highest supported depth (in samples).
+    const MAX_DEPTH: usize = 512;
+
+    #[must_use]
+    pub fn new(rate: f32, depth: f32) -> Self {
+        let size = Self::MAX_DEPTH * 2 + 3;
+        let mut res = Self {
+            left: DelayLine::new(size),
+            right: DelayLine::new(size),
+            step: rate * 8. * SAMPLE_DURATION,
+            phase: 0.,
+            depth: depth.clamp(0., Self::MAX_DEPTH as f32),
+            prev: 0.,
+            stereo_tail: 0,
+        };
+        res.prev = res.delay();
+        res
+    }
+
+    /// Calculate the current delay time.
+    ///
+    /// The delay swings around the depth, so it never goes below 1 sample.
+    fn delay(&self) -> f32 {
+        let lfo = F32Ext::sin(core::f32::consts::TAU * self.phase);
+        F32Ext::mul_add(lfo, self.depth, self.depth + 1.)
+    }
+}
+
+impl Processor for Vibrato {
+    fn reset(&mut self) {
+        self.left.reset();
+        self.right.reset();
+        self.phase = 0.;
+        self.prev = self.delay();
+        self.stereo_tail = 0;
+    }
+
+    fn set(&mut self, param: u8, val: f32) {
+        match param {
+            0 => self.step = val * 8. * SAMPLE_DURATION,
+            1 => self.depth = val.clamp(0., Self::MAX_DEPTH as f32),
+            _ => {}
+        }
+    }
+
+    fn process_frame(&mut self, f: Frame) -> Option<Frame> {
+        self.phase = F32Ext::fract(self.phase + self.step);
+        let start = self.prev;
+        let end = self.delay();
+        self.prev = end;
+
+        // Mono input is written into both delay lines, so that the right channel
+        // has the correct history when the input switches to stereo.
+        // The output stays stereo until the delayed stereo input is emitted.
+        if f.right.is_some() {
+            self.stereo_tail = Self::MAX_DEPTH * 2 + 2;
+        }
+        let stereo = self.stereo_tail > 0;
+        if f.right.is_none() {
+            self.stereo_tail = self.stereo_tail.saturating_sub(8);
+        }
+
+        let mut left = f.left.to_array();
+        let mut right = f.right.unwrap_or(f.left).to_array();
+        for (i, (l, r)) in left.iter_mut().zip(right.iter_mut()).enumerate() {
+            let delay = F32Ext::mul_add(end - start, (i + 1) as f32 / 8., start);
+            self.left.write(*l);
+            *l = self.left.read(delay);
+            self.right.write(*r);
+            *r = self.right.read(delay);
+        }
+        let left = Sample::new(left);
+        if stereo {
+            Some(Frame::stereo(left, Sample::new(right)))
+        } else {
+            Some(Frame::mono(left))
+        }
+    }
+}
+
+/// The waveform of the [`Tremolo`] LFO.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LfoShape {
+    Sine,
+    Triangle,
+    /// Switch between full and reduced volume, for a choppy sound.
+    Square,
+    /// Ramp up and then abruptly drop.
+    Sawtooth,
+}
+
+impl LfoShape {
+    fn from_param(val: f32) -> Self {
+        if val < 0.5 {
+            Self::Sine
+        } else if val < 1.5 {
+            Self::Triangle
+        } else if val < 2.5 {
+            Self::Square
+        } else {
+            Self::Sawtooth
+        }
+    }
+
+    /// Calculate the LFO value (from 0 to 1) for the given phases (from 0 to 1).
+    fn apply(self, phases: Sample) -> Sample {
+        match self {
+            Self::Sine => ((phases * Sample::TAU).sin() + 1.) * 0.5,
+            Self::Triangle => Sample::ONE - (phases * 2. - 1.).abs(),
+            Self::Square => (phases * 2.).floor(),
+            Self::Sawtooth => phases,
+        }
+    }
+}
+
+/// Periodically change the volume.
+///
+/// Params:
+///
+/// 0. LFO rate (in Hz).
+/// 1. Depth: from 0 (no effect) to 1 (the volume goes down to silence).
+/// 2. Shape: 0 is [`LfoShape::Sine`], 1 is [`LfoShape::Triangle`],
+///    2 is [`LfoShape::Square`], and 3 is [`LfoShape::Sawtooth`].
+pub struct Tremolo {
+    shape: LfoShape,
+    /// The LFO phase advance on each sample.
+    step: f32,
+    phase: f32,
+    depth: f32,
+}
+
+impl Tremolo {
+    #[must_use]
+    pub fn new(shape: LfoShape, rate: f32, depth: f32) -> Self {
+        Self {
+            shape,
+            step: rate * SAMPLE_DURATION,
+            phase: 0.,
+            depth,
+        }
+    }
+}
+
+impl Processor for Tremolo {
+    fn reset(&mut self) {
+        self.phase = 0.;
+    }
+
+    fn set(&mut self, param: u8, val: f32) {
+        match param {
+            0 => self.step = val * SAMPLE_DURATION,
+            1 => self.depth = val.clamp(0., 1.),
+            2 => self.shape = LfoShape::from_param(val),
+            _ => {}
+        }
+    }
+
+    fn process_frame(&mut self, f: Frame) -> Option<Frame> {
+        let mut phases = [0f32; 8];
+        let mut phase = self.phase;
+        for sample in &mut phases {
+            *sample = phase;
+            phase = F32Ext::fract(phase + self.step);
+        }
+        self.phase = phase;
+        let lfo = self.shape.apply(Sample::new(phases));
+        let gain = Sample::ONE - lfo * self.depth;
+        let left = f.left * gain;
+        let right = f.right.map(|s| s * gain);
+        Some(Frame { left, right })
+    }
+}
+
+/// Sweep notches through the spectrum by mixing the input with its copy
+/// passed through a chain of LFO-modulated all-pass filters.
+///
+/// If the input is mono and there is no phase offset between channels,
+/// the output is mono as well. Params:
+///
+/// 0. LFO rate (in Hz).
+/// 1. Depth: from 0 to 1, sweeps up to 2 octaves in each direction from the center.
+/// 2. Feedback: how much of the filtered signal is fed back into the chain.
+///    Can be negative.
+/// 3. Center frequency of the sweep (in Hz).
+/// 4. Mix: 0 is only the dry signal, 1 is only the filtered signal.
+///    The notches are the deepest at 0.5.
+/// 5. The number of all-pass stages: 4, 6, or 8.
+/// 6. Phase offset of the right channel LFO, from 0 to 1 (a full period).
+pub struct Phaser {
+    stages: usize,
+    /// The LFO phase advance on each frame.
+    step: f32,
+    phase: f32,
+    offset: f32,
+    depth: f32,
+    feedback: f32,
+    center: f32,
+    mix: f32,
+    /// The all-pass coefficients at the end of the previous frame.
+    prev: (f32, f32),
+    left: PhaserState,
+    right: PhaserState,
+}
+
+impl Phaser {
+    #[must_use]
+    pub fn new(stages: u8, rate: f32, depth: f32) -> Self {
+        let mut res = Self {
+            stages: phaser_stages(f32::from(stages)),
+            step: rate * 8. * SAMPLE_DURATION,
+            phase: 0.,
+            offset: 0.,
+            depth,
+            feedback: 0.,
+            center: 1000.,
+            mix: 0.5,
+            prev: (0., 0.),
+            left: PhaserState::default(),
+            right: PhaserState::default(),
+        };
+        res.prev = res.coefs();
+        res
+    }
+
+    /// Calculate the current all-pass coefficients for both channels.
+    fn coefs(&self) -> (f32, f32) {
+        let coef = |phase: f32| {
+            let lfo = F32Ext::sin(core::f32::consts::TAU * phase);
+            let freq = self.center * F32Ext::powf(2., 2. * self.depth * lfo);
+            let freq = freq.clamp(10., SAMPLE_RATE as f32 * 0.49);
+            let t = F32Ext::tan(core::f32::consts::PI * freq * SAMPLE_DURATION);
+            (t - 1.) / (t + 1.)
+        };
+        let right_phase = F32Ext::fract(self.phase + self.offset);
+        (coef(self.phase), coef(right_phase))
+    }
+}
+
+/// Round the number of stages to one of the supported values.
+#[expect(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+const fn phaser_stages(val: f32) -> usize {
+    let stages = val.clamp(4., 8.) as usize;
+    stages & !1
+}
+
+impl Processor for Phaser {
+    fn reset(&mut self) {
+        self.left = PhaserState::default();
+        self.right = PhaserState::default();
+        self.phase = 0.;
+        self.prev = self.coefs();
+    }
+
+    fn set(&mut self, param: u8, val: f32) {
+        match param {
+            0 => self.step = val * 8. * SAMPLE_DURATION,
+            1 => self.depth = val.clamp(0., 1.),
+            2 => self.feedback = val.clamp(-0.95, 0.95),
+            3 => self.center = val,
+            4 => self.mix = val,
+            5 => self.stages = phaser_stages(val),
+            6 => self.offset = val,
+            _ => {}
+        }
+    }
+
+    fn process_frame(&mut self, f: Frame) -> Option<Frame> {
+        self.phase = F32Ext::fract(self.phase + self.step);
+        let (start_left, start_right) = self.prev;
+        let (end_left, end_right) = self.coefs();
+        self.prev = (end_left, end_right);
+
+        let params = (self.stages, self.feedback, self.mix);
+        let left = self.left.process(f.left, start_left, end_left, params);
+        // The right chain always runs, so that its state is up to date
+        // when the input switches to stereo or the phase offset changes.
+        let right = f.right.unwrap_or(f.left);
+        let right = self.right.process(right, start_right, end_right, params);
+        let stereo = f.right.is_some() || self.offset != 0.;
+        let right = if stereo { Some(right) } else { None };
+        Some(Frame { left, right })
+    }
+}
+
+/// Per-channel state of [`Phaser`].
+#[derive(Default)]
+struct PhaserState {
+    /// The state of each all-pass filter.
+    stages: [f32; 8],
+    /// The last output of the all-pass chain, used for feedback.
+    last: f32,
+}
+
+impl PhaserState {
+    fn process(
+        &mut self,
+        s: Sample,
+        start: f32,
+        end: f32,
+        (stages, feedback, mix): (usize, f32, f32),
+    ) -> Sample {
+        let mut samples = s.to_array();
+        for (i, x) in samples.iter_mut().enumerate() {
+            let coef = F32Ext::mul_add(end - start, (i + 1) as f32 / 8., start);
+            let mut y = F32Ext::mul_add(self.last, feedback, *x);
+            for state in &mut self.stages[..stages] {
+                let out = F32Ext::mul_add(coef, y, *state);
+                *state = F32Ext::mul_add(-coef, out, y);
+                y = out;
+            }
+            self.last = y;
+            *x = F32Ext::mul_add(*x, 1. - mix, y * mix);
+        }
+        Sample::new(samples)
+    }
+}
+
+/// Noise gate and downward expander.
+///
+/// Attenuates the signal while its level is below the threshold.
+/// By default, the level is measured on the input itself (the mix of all children),
+/// but it can be measured on one of the children instead using [`Gate::set_sidechain`].
+/// Params:
+///
+/// 0. Threshold (in dB): the gate opens when the level goes above it.
+/// 1. Hysteresis (in dB): the gate closes only when the level goes below
+///    the threshold minus hysteresis. Prevents chattering around the threshold.
+/// 2. Attack (in samples): how fast the gate opens.
+/// 3. Hold (in samples): how long the gate stays open after the level drops.
+/// 4. Release (in samples): how fast the gate closes.
+/// 5. Range (in dB): the attenuation when the gate is closed. Use a large negative value
+///    (like -100) for a gate and a small one (like -10) for an expander.
+pub struct Gate {
+    open_at: f32,
+    close_at: f32,
+    threshold: f32,
+    hysteresis: f32,
+    attack: f32,
+    hold: u32,
+    release: f32,
+    range: f32,
+    sidechain: Option<u8>,
+
+    open: bool,
+    envelope: f32,
+    gain: f32,
+    /// How many samples left until the gate can start closing.
+    holding: u32,
+}
+
+impl Gate {
+    /// How fast the level detector follows the signal going down.
+    const ENVELOPE_DECAY: f32 = 0.995;
+
+    /// Create a gate with 3 dB hysteresis and full attenuation when closed.
+    #[must_use]
+    pub fn new(threshold: f32, attack: f32, hold: Position, release: f32) -> Self {
+        let mut res = Self {
+            open_at: 0.,
+            close_at: 0.,
+            threshold,
+            hysteresis: 3.,
+            attack: time_coef(attack),
+            hold,
+            release: time_coef(release),
+            range: 0.,
+            sidechain: None,
+            open: false,
+            envelope: 0.,
+            gain: 0.,
+            holding: 0,
+        };
+        res.update_thresholds();
+        res
+    }
+
+    /// Measure the level on the child with the given index instead of the input.
+    ///
+    /// The sidechain child is used only to open and close the gate,
+    /// it's not included in the output. If there is no child with the given index,
+    /// the level is measured on the input.
+    pub const fn set_sidechain(&mut self, child: Option<u8>) {
+        self.sidechain = child;
+    }
+
+    fn update_thresholds(&mut self) {
+        self.open_at = db_to_amp(self.threshold);
+        self.close_at = db_to_amp(self.threshold - self.hysteresis);
+    }
+
+    /// Attenuate the frame based on the level of the key frame.
+    fn apply(&mut self, f: &Frame, key: &Frame) -> Frame {
+        let mut left = f.left.to_array();
+        let mut right = f.right.map(Sample::to_array);
+        let key_left = key.left.as_array();
+        let key_right = key.right.as_ref().map(Sample::as_array);
+        for (i, l) in left.iter_mut().enumerate() {
+            let mut peak = key_left[i].abs();
+            if let Some(key_right) = key_right {
+                peak = peak.max(key_right[i].abs());
+            }
+            self.envelope = peak.max(self.envelope * Self::ENVELOPE_DECAY);
+
+            if self.envelope >= self.open_at {
+                self.open = true;
+            } else if self.envelope < self.close_at {
+                self.open = false;
+            }
+            // The hold starts counting down only when the gate closes.
+            if self.open {
+                self.holding = self.hold;
+            }
+            if self.open || self.holding > 0 {
+                if !self.open {
+                    self.holding -= 1;
+                }
+                self.gain = F32Ext::mul_add(self.gain - 1., self.attack, 1.);
+            } else {
+                self.gain = F32Ext::mul_add(self.gain - self.range, self.release, self.range);
+                // Snap to the target to avoid slow subnormal numbers.
+                if self.gain - self.range < 1e-6 {
+                    self.gain = self.range;
+                }
+            }
+
+            *l *= self.gain;
+            if let Some(right) = &mut right {
+                right[i] *= self.gain;
+            }
+        }
+        let left = Sample::new(left);
+        let right = right.map(Sample::new);
+        Frame { left, right }
+    }
+}
+
+impl Processor for Gate {
+    fn reset(&mut self) {
+        self.open = false;
+        self.envelope = 0.;
+        self.gain = self.range;
+        self.holding = 0;
+    }
+
+    fn set(&mut self, param: u8, val: f32) {
+        match param {
+            0 => {
+                self.threshold = val;
+                self.update_thresholds();
+            }
+            1 => {
+                self.hysteresis = val.max(0.);
+                self.update_thresholds();
+            }
+            2 => self.attack = time_coef(val),
+            #[expect(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            3 => self.hold = val as u32,
+            4 => self.release = time_coef(val),
+            5 => self.range = db_to_amp(val.min(0.)),
+            _ => {}
+        }
+    }
+
+    fn process_children(&mut self, cn: &mut [Node]) -> Option<Frame> {
+        let sidechain = self.sidechain.filter(|i| usize::from(*i) < cn.len());
+        let Some(sidechain) = sidechain else {
+            let f = Mix::new().process_children(cn)?;
+            return self.process_frame(f);
+        };
+        let mut sum = Frame::zero();
+        let mut empty = true;
+        let mut key = Frame::zero();
+        for (i, node) in cn.iter_mut().enumerate() {
+            let Some(frame) = node.next_frame() else {
+                continue;
+            };
+            if i == usize::from(sidechain) {
+                key = frame;
+            } else {
+                sum = sum + frame;
+                empty = false;
+            }
+        }
+        if empty {
+            return None;
+        }
+        Some(self.apply(&sum, &key))
+    }
+
+    fn process_frame(&mut self, f: Frame) -> Option<Frame> {
+        Some(self.apply(&f, &f))
+    }
+}
+
+/// Lower the volume of the main input while the key input is playing.
+///
+/// Useful for making the music quieter under dialogue and sound effects.
+/// The first child is the main input and all other children are the key input
+/// (see [`Duck::set_main_children`] to change that).
+/// The output is the ducked main input mixed with the key input. Params:
+///
+/// 0. Depth (in dB): the attenuation of the main input while the key is playing.
+/// 1. Attack (in samples): how fast the main input is ducked when the key starts.
+/// 2. Release (in samples): how fast the main input recovers when the key stops.
+/// 3. Threshold (in dB): the key level above which it is considered playing.
+pub struct Duck {
+    main_children: u8,
+    depth: f32,
+    attack: f32,
+    release: f32,
+    threshold: f32,
+    /// The peak level of the key input.
+    envelope: f32,
+    /// How much the main input is currently ducked, from 0 to 1.
+    amount: f32,
+}
+
+impl Duck {
+    #[must_use]
+    pub fn new(depth: f32, attack: f32, release: f32) -> Self {
+        Self {
+            main_children: 1,
+            depth: db_to_amp(depth.min(0.)),
+            attack: time_coef(attack),
+            release: time_coef(release),
+            threshold: db_to_amp(-40.),
+            envelope: 0.,
+            amount: 0.,
+        }
+    }
+
+    /// Set how many of the first children are the main input.
+    ///
+    /// All the children after them are the key input.
+    pub const fn set_main_children(&mut self, n: u8) {
+        self.main_children = n;
+    }
+
+    fn apply(&mut self, main: &Frame, key: &Frame) -> Frame {
+        let mut left = main.left.to_array();
+        let mut right = main.right.map(Sample::to_array);
+        let key_left = key.left.as_array();
+        let key_right = key.right.as_ref().map(Sample::as_array);
+        for (i, l) in left.iter_mut().enumerate() {
+            let mut peak = key_left[i].abs();
+            if let Some(key_right) = key_right {
+                peak = peak.max(key_right[i].abs());
+            }
+            self.envelope = peak.max(self.envelope * Gate::ENVELOPE_DECAY);
+            let (target, coef) = if self.envelope >= self.threshold {
+                (1., self.attack)
+            } else {
+                (0., self.release)
+            };
+            self.amount = F32Ext::mul_add(self.amount - target, coef, target);
+            if self.amount < 1e-6 {
+                self.amount = 0.;
+            }
+            let gain = F32Ext::mul_add(self.depth - 1., self.amount, 1.);
+            *l *= gain;
+            if let Some(right) = &mut right {
+                right[i] *= gain;
+            }
+        }
+        let left = Sample::new(left);
+        let right = right.map(Sample::new);
+        Frame { left, right } + key.clone()
+    }
+}
+
+impl Processor for Duck {
+    fn reset(&mut self) {
+        self.envelope = 0.;
+        self.amount = 0.;
+    }
+
+    fn set(&mut self, param: u8, val: f32) {
+        match param {
+            0 => self.depth = db_to_amp(val.min(0.)),
+            1 => self.attack = time_coef(val),
+            2 => self.release = time_coef(val),
+            3 => self.threshold = db_to_amp(val),
+            _ => {}
+        }
+    }
+
+    fn process_children(&mut self, cn: &mut [Node]) -> Option<Frame> {
+        let mut main = Frame::zero();
+        let mut key = Frame::zero();
+        let mut empty = true;
+        for (i, node) in cn.iter_mut().enumerate() {
+            let Some(frame) = node.next_frame() else {
+                continue;
+            };
+            empty = false;
+            if i < usize::from(self.main_children) {
+                main = main + frame;
+            } else {
+                key = key + frame;
+            }
+        }
+        if empty {
+            return None;
+        }
+        Some(self.apply(&main, &key))
+    }
+}
+
+/// Measure the level of the audio passing through it.
+///
+/// The audio itself is passed forward with no changes. The measured level
+/// can be used to modulate a parameter of another node: get a modulator
+/// using [`EnvelopeFollower::modulator`] before adding the node into the tree
+/// and pass it into [`Node::modulate`]. For example, the loudness of
+/// a drum loop can open a filter on a pad. Params:
+///
+/// 0. Attack (in samples): how fast the level follows the signal going up.
+/// 1. Release (in samples): how fast the level follows the signal going down.
+pub struct EnvelopeFollower {
+    attack: f32,
+    release: f32,
+    envelope: f32,
+    level: Rc<Cell<f32>>,
+}
+
+impl EnvelopeFollower {
+    #[must_use]
+    pub fn new(attack: f32, release: f32) -> Self {
+        Self {
+            attack: time_coef(attack),
+            release: time_coef(release),
+            envelope: 0.,
+            level: Rc::new(Cell::new(0.)),
+        }
+    }
+
+    /// Get a modulator producing the level measured by this node.
+    ///
+    /// There can be any number of modulators for the same follower.
+    /// The modulated node sees the level measured on the previous frame
+    /// if it is processed before the follower.
+    #[must_use]
+    pub fn modulator(&self) -> modulators::Follower {
+        modulators::Follower::new(Rc::clone(&self.level))
+    }
+}
+
+impl Processor for EnvelopeFollower {
+    fn reset(&mut self) {
+        self.envelope = 0.;
+        self.level.set(0.);
+    }
+
+    fn set(&mut self, param: u8, val: f32) {
+        match param {
+            0 => self.attack = time_coef(val),
+            1 => self.release = time_coef(val),
+            _ => {}
+        }
+    }
+
+    fn process_frame(&mut self, f: Frame) -> Option<Frame> {
+        let mut peaks = f.left.abs();
+        if let Some(right) = &f.right {
+            peaks = peaks.fast_max(right.abs());
+        }
+        for peak in peaks.to_array() {
+            let coef = if peak > self.envelope {
+                self.attack
+            } else {
+                self.release
+            };
+            self.envelope = F32Ext::mul_add(self.envelope - peak, coef, peak);
+        }
+        self.level.set(self.envelope);
+        Some(f)
+    }
+}
+
+/// Remove the DC offset (constant bias) from the signal.
+///
+/// The DC offset wastes headroom and causes clicks when the sound starts
+/// or stops. It is often introduced by 8-bit audio converted from unsigned
+/// samples or by asymmetric saturation (like [`Waveshape::Tube`]).
+/// Can be applied to the whole mix using [`Manager::set_dc_blocker`]. Params:
+///
+/// 0. Cutoff frequency (in Hz). Frequencies below it are attenuated.
+///    Usually, 5-20 Hz.
+pub struct DcBlocker {
+    /// The pole of the filter, close to but below 1.
+    pole: f32,
+    left: DcBlockerState,
+    right: DcBlockerState,
+}
+
+impl DcBlocker {
+    #[must_use]
+    pub fn new(cutoff: f32) -> Self {
+        Self {
+            pole: dc_blocker_pole(cutoff),
+            left: DcBlockerState::default(),
+            right: DcBlockerState::default(),
+        }
+    }
+}
+
+fn dc_blocker_pole(cutoff: f32) -> f32 {
+    let w = core::f32::consts::TAU * cutoff.max(0.) * SAMPLE_DURATION;
+    F32Ext::exp(-w)
+}
+
+impl Processor for DcBlocker {
+    fn reset(&mut self) {
+        self.left = DcBlockerState::default();
+        self.right = DcBlockerState::default();
+    }
+
+    fn set(&mut self, param: u8, val: f32) {
+        if param == 0 {
+            self.pole = dc_blocker_pole(val);
+        }
+    }
+
+    fn process_frame(&mut self, f: Frame) -> Option<Frame> {
+        let left = self.left.process(f.left, self.pole);
+        // Mono input is filtered for both channels, so that the right channel
+        // doesn't click when the input switches to stereo.
+        let right = self.right.process(f.right.unwrap_or(f.left), self.pole);
+        let right = f.right.map(|_| right);
+        Some(Frame { left, right })
+    }
+}
+
+/// Per-channel state of [`DcBlocker`].
+#[derive(Default)]
+struct DcBlockerState {
+    x1: f32,
+    y1: f32,
+}
+
+impl DcBlockerState {
+    fn process(&mut self, s: Sample, pole: f32) -> Sample {
+        let mut samples = s.to_array();
+        for x in &mut samples {
+            let y = F32Ext::mul_add(pole, self.y1, *x - self.x1);
+            self.x1 = *x;
+            self.y1 = y;
+            *x = y;
+        }
+        Sample::new(samples)
+    }
+}
+
+/// Convolve the signal with an impulse response (IR).
+///
+/// Applies the acoustics captured in a short recording: speaker cabinets,
+/// small rooms, telephone or radio coloration, etc. The same (mono) IR
+/// is applied to both channels.
+///
+/// Short IRs (up to 64 samples) are applied directly, with no latency.
+/// Longer IRs use uniformly partitioned FFT convolution which delays
+/// the output by 64 samples. The CPU time and memory grow linearly
+/// with the IR length: for each 64 samples of IR, 2 KB of memory. Params:
+///
+/// 0. Mix: 0 is only the dry signal, 1 is only the convolved signal.
+pub struct Convolution {
+    mix: f32,
+    kernel: ConvolutionKernel,
+    /// For how long (in samples) the output is affected by an input sample.
+    tail: usize,
+    /// How many samples of the output are still affected by stereo input.
+    stereo_tail: usize,
+}
+
+enum ConvolutionKernel {
+    Direct(DirectConvolver),
+    Fft(FftConvolver),
+}
+
+impl Convolution {
+    /// The partition size for FFT convolution (in samples), also the output latency.
+    const BLOCK: usize = 64;
+
+    #[must_use]
+    pub fn new(ir: &[f32]) -> Self {
+        let kernel = if ir.len() <= Self::BLOCK {
+            ConvolutionKernel::Direct(DirectConvolver::new(ir))
+        } else {
+            ConvolutionKernel::Fft(FftConvolver::new(ir, Self::BLOCK))
+        };
+        Self {
+            mix: 1.,
+            kernel,
+            tail: ir.len() + Self::BLOCK,
+            stereo_tail: 0,
+        }
+    }
+}
+
+impl Processor for Convolution {
+    fn reset(&mut self) {
+        match &mut self.kernel {
+            ConvolutionKernel::Direct(kernel) => kernel.reset(),
+            ConvolutionKernel::Fft(kernel) => kernel.reset(),
+        }
+        self.stereo_tail = 0;
+    }
+
+    fn set(&mut self, param: u8, val: f32) {
+        if param == 0 {
+            self.mix = val;
+        }
+    }
+
+    fn process_frame(&mut self, f: Frame) -> Option<Frame> {
+        // Mono input is convolved for both channels, so that the right channel
+        // has the correct history when the input switches to stereo.
+        // The output stays stereo until the tail of stereo input fades out.
+        if f.right.is_some() {
+            self.stereo_tail = self.tail;
+        }
+        let stereo = self.stereo_tail > 0;
+        if f.right.is_none() {
+            self.stereo_tail = self.stereo_tail.saturating_sub(8);
+        }
+
+        let mut left = f.left.to_array();
+        let mut right = f.right.unwrap_or(f.left).to_array();
+        let dry = 1. - self.mix;
+        for (l, r) in left.iter_mut().zip(right.iter_mut()) {
+            // Both channels are convolved at once: the left one is the real part
+            // and the right one is the imaginary part. It works because the IR is real.
+            let input = Complex::new(*l, *r);
+            let (dry_sample, wet) = match &mut self.kernel {
+                ConvolutionKernel::Direct(kernel) => (input, kernel.process(input)),
+                ConvolutionKernel::Fft(kernel) => kernel.process(input),
+            };
+            *l = F32Ext::mul_add(dry_sample.re, dry, wet.re * self.mix);
+            *r = F32Ext::mul_add(dry_sample.im, dry, wet.im * self.mix);
+        }
+        let left = Sample::new(left);
+        if stereo {
+            Some(Frame::stereo(left, Sample::new(right)))
+        } else {
+            Some(Frame::mono(left))
+        }
+    }
+}
+
+/// Time-domain convolution, for short IRs.
+struct DirectConvolver {
+    ir: Vec<f32>,
+    /// The most recent inputs, as a ring buffer.
+    history: Vec<Complex>,
+    pos: usize,
+}
+
+impl DirectConvolver {
+    fn new(ir: &[f32]) -> Self {
+        let len = ir.len().max(1);
+        Self {
+            ir: ir.to_vec(),
+            history: vec![Complex::default(); len],
+            pos: 0,
+        }
+    }
+
+    fn reset(&mut self) {
+        self.history.fill(Complex::default());
+        self.pos = 0;
+    }
+
+    fn process(&mut self, input: Complex) -> Complex {
+        self.history[self.pos] = input;
+        let len = self.history.len();
+        let mut acc = Complex::default();
+        for (k, coef) in self.ir.iter().enumerate() {
+            let x = self.history[(self.pos + len - k) % len];
+            acc.re = F32Ext::mul_add(x.re, *coef, acc.re);
+            acc.im = F32Ext::mul_add(x.im, *coef, acc.im);
+        }
+        self.pos = (self.pos + 1) % len;
+        acc
+    }
+}
+
+/// Uniformly partitioned overlap-save convolution, for long IRs.
+struct FftConvolver {
+    fft: Fft,
+    block: usize,
+    /// The spectrum of each IR partition.
+    partitions: Vec<Vec<Complex>>,
+    /// The spectra of the most recent input blocks, as a ring buffer.
+    history: Vec<Vec<Complex>>,
+    history_pos: usize,
+    /// The previous input block followed by the current (incomplete) one.
+    input: Vec<Complex>,
+    /// The output for the previous input block.
+    output: Vec<Complex>,
+    /// Working memory for the FFT.
+    scratch: Vec<Complex>,
+    /// The position in the current block.
+    pos: usize,
+}
+
+impl FftConvolver {
+    fn new(ir: &[f32], block: usize) -> Self {
+        let size = block * 2;
+        let fft = Fft::new(size);
+        let mut partitions = Vec::new();
+        for chunk in ir.chunks(block) {
+            let mut spectrum = vec![Complex::default(); size];
+            for (c, s) in spectrum.iter_mut().zip(chunk) {
+                c.re = *s;
+            }
+            fft.forward(&mut spectrum);
+            partitions.push(spectrum);
+        }
+        let history = vec![vec![Complex::default(); size]; partitions.len()];
+        Self {
+            fft,
+            block,
+            partitions,
+            history,
+            history_pos: 0,
+            input: vec![Complex::default(); size],
+            output: vec![Complex::default(); block],
+            scratch: vec![Complex::default(); size],
+            pos: 0,
+        }
+    }
+
+    fn reset(&mut self) {
+        for spectrum in &mut self.history {
+            spectrum.fill(Complex::default());
+        }
+        self.input.fill(Complex::default());
+        self.output.fill(Complex::default());
+        self.pos = 0;
+    }
+
+    /// Push the next input sample, get the delayed dry and convolved samples.
+    fn process(&mut self, input: Complex) -> (Complex, Complex) {
+        let dry = self.input[self.pos];
+        let wet = self.output[self.pos];
+        self.input[self.block + self.pos] = input;
+        self.pos += 1;
+        if self.pos == self.block {
+            self.pos = 0;
+            self.process_block();
+        }
+        (dry, wet)
+    }
+
+    fn process_block(&mut self) {
+        let spectrum = &mut self.history[self.history_pos];
+        spectrum.copy_from_slice(&self.input);
+        self.fft.forward(spectrum);
+
+        // Multiply each past input block by the matching IR partition and sum it all up.
+        self.scratch.fill(Complex::default());
+        let len = self.history.len();
+        for (i, partition) in self.partitions.iter().enumerate() {
+            let spectrum = &self.history[(self.history_pos + len - i) % len];
+            for ((acc, x), h) in self.scratch.iter_mut().zip(spectrum).zip(partition) {
+                *acc = *acc + *x * *h;
+            }
+        }
+        self.history_pos = (self.history_pos + 1) % len;
+
+        // The first half of the result is wrapped around, keep only the second half.
+        self.fft.inverse(&mut self.scratch);
+        let scale = 1. / self.scratch.len() as f32;
+        for (out, res) in self.output.iter_mut().zip(&self.scratch[self.block..]) {
+            *out = Complex::new(res.re * scale, res.im * scale);
+        }
+        self.input.copy_within(self.block.., 0);
+    }
+}
+
+/// Change the pitch without changing the duration.
+///
+/// Uses the "rotating tape head" technique: the input is read from a delay line
+/// by two taps moving faster or slower than the input is written,
+/// crossfading whenever a tap wraps around. Params:
+///
+/// 0. Semitones: how much to shift the pitch up (positive) or down (negative).
+/// 1. Cents: a fine adjustment on top of semitones, 100 cents in a semitone.
+///
+/// The pitch changes continuously, so both params can be modulated.
+pub struct PitchShift {
+    left: DelayLine,
+    right: DelayLine,
+    semitones: f32,
+    cents: f32,
+    /// How fast the taps move relative to the input, per sample.
+    step: f32,
+    /// The position of the first tap in the window, from 0 to 1.
+    phase: f32,
+    /// How many samples of the output are still affected by stereo input.
+    stereo_tail: usize,
+}
+
+impl PitchShift {
+    /// The length of the window (in samples) the taps are moving through.
+    const WINDOW: usize = 2048;
+
+    #[must_use]
+    pub fn new(semitones: f32) -> Self {
+        let size = Self::WINDOW + 3;
+        let mut res = Self {
+            left: DelayLine::new(size),
+            right: DelayLine::new(size),
+            semitones,
+            cents: 0.,
+            step: 0.,
+            phase: 0.,
+            stereo_tail: 0,
+        };
+        res.update_step();
+        res
+    }
+
+    fn update_step(&mut self) {
+        let ratio = F32Ext::powf(2., (self.semitones + self.cents / 100.) / 12.);
+        self.step = (1. - ratio) / Self::WINDOW as f32;
+    }
+}
+
+impl Processor for PitchShift {
+    fn reset(&mut self) {
+        self.left.reset();
+        self.right.reset();
+        self.phase = 0.;
+        self.stereo_tail = 0;
+    }
+
+    fn set(&mut self, param: u8, val: f32) {
+        match param {
+            0 => self.semitones = val,
+            1 => self.cents = val,
+            _ => return,
+        }
+        self.update_step();
+    }
+
+    fn process_frame(&mut self, f: Frame) -> Option<Frame> {
+        // Mono input is written into both delay lines, so that the right channel
+        // has the correct history when the input switches to stereo.
+        // The output stays stereo until the delayed stereo input is emitted.
+        if f.right.is_some() {
+            self.stereo_tail = Self::WINDOW + 2;
+        }
+        let stereo = self.stereo_tail > 0;
+        if f.right.is_none() {
+            self.stereo_tail = self.stereo_tail.saturating_sub(8);
+        }
+
+        let window = Self::WINDOW as f32;
+        let mut left = f.left.to_array();
+        let mut right = f.right.unwrap_or(f.left).to_array();
+        for (l, r) in left.iter_mut().zip(right.iter_mut()) {
+            self.phase = F32Ext::fract(self.phase + self.step + 1.);
+            let phase2 = F32Ext::fract(self.phase + 0.5);
+            let delay1 = F32Ext::mul_add(self.phase, window, 1.);
+            let delay2 = F32Ext::mul_add(phase2, window, 1.);
+            // The tap is silent when wrapping around and the loudest in the middle.
+            let sin = F32Ext::sin(core::f32::consts::PI * self.phase);
+            let gain1 = sin * sin;
+            let gain2 = 1. - gain1;
+
+            self.left.write(*l);
+            *l = F32Ext::mul_add(
+                self.left.read(delay1),
+                gain1,
+                self.left.read(delay2) * gain2,
+            );
+            self.right.write(*r);
+            *r = F32Ext::mul_add(
+                self.right.read(delay1),
+                gain1,
+                self.right.read(delay2) * gain2,
+            );
+        }
+        let left = Sample::new(left);
+        if stereo {
+            Some(Frame::stereo(left, Sample::new(right)))
+        } else {
+            Some(Frame::mono(left))
+        }
+    }
+}
+
+/// Play the children faster or slower, changing both tempo and pitch,
+/// like a tape or a vinyl record.
+///
+/// The children are resampled with cubic interpolation. Params:
+///
+/// 0. Speed: 1 is the normal speed, 0.5 is twice slower, 2 is twice faster,
+///    0 stops the playback. Can be modulated for tape stop effects.
+///    When approaching 0, the sound fades out, so that the stopped
+///    playback is silent instead of being stuck on the last sample.
+pub struct Speed {
+    ratio: f32,
+    /// The read position in the buffer.
+    pos: f32,
+    /// The last 3 samples of the previous frame followed by the current frame.
+    left: [f32; 11],
+    right: [f32; 11],
+    stereo: bool,
+    ended: bool,
+}
+
+impl Speed {
+    /// The highest supported speed.
+    const MAX_SPEED: f32 = 16.;
+    /// The number of samples from the previous frame kept in the buffer.
+    const HISTORY: usize = 3;
+    /// Below this speed, the sound fades out, reaching silence at zero speed.
+    const STOP_SPEED: f32 = 0.05;
+
+    #[must_use]
+    pub const fn new(speed: f32) -> Self {
+        Self {
+            ratio: speed.clamp(0., Self::MAX_SPEED),
+            pos: Self::first_pos(),
+            left: [0.; 11],
+            right: [0.; 11],
+            stereo: false,
+            ended: false,
+        }
+    }
+
+    /// The initial position: the very beginning of a frame that is not yet pulled.
+    const fn first_pos() -> f32 {
+        (Self::HISTORY + 8) as f32
+    }
+
+    /// Pull the next frame from the children into the buffer.
+    ///
+    /// Returns false if the children have ended.
+    fn pull(&mut self, cn: &mut [Node]) -> bool {
+        let Some(f) = Mix::new().process_children(cn) else {
+            return false;
+        };
+        self.left.copy_within(8.., 0);
+        self.right.copy_within(8.., 0);
+        self.left[Self::HISTORY..].copy_from_slice(f.left.as_array());
+        let right = f.right.as_ref().unwrap_or(&f.left);
+        self.right[Self::HISTORY..].copy_from_slice(right.as_array());
+        self.stereo = f.right.is_some();
+        self.pos -= 8.;
+        true
+    }
+
+    /// Pull as many frames as needed to reach the read position.
+    ///
+    /// Returns false if the children have ended.
+    #[expect(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn pull_until_ready(&mut self, cn: &mut [Node]) -> bool {
+        let frames = (self.pos - 1.) as usize / 8;
+        for _ in 0..frames {
+            if !self.pull(cn) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+impl Processor for Speed {
+    fn reset(&mut self) {
+        self.pos = Self::first_pos();
+        self.left = [0.; 11];
+        self.right = [0.; 11];
+        self.ended = false;
+    }
+
+    fn set(&mut self, param: u8, val: f32) {
+        if param == 0 {
+            self.ratio = val.clamp(0., Self::MAX_SPEED);
+        }
+    }
+
+    #[expect(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn process_children(&mut self, cn: &mut [Node]) -> Option<Frame> {
+        if self.ended {
+            return None;
+        }
+        if self.ratio <= 0. {
+            return Some(if self.stereo {
+                Frame::stereo(Sample::ZERO, Sample::ZERO)
+            } else {
+                Frame::zero()
+            });
+        }
+        let mut left = [0f32; 8];
+        let mut right = [0f32; 8];
+        for (l, r) in left.iter_mut().zip(right.iter_mut()) {
+            // Cubic interpolation needs one sample before and two after the position.
+            if self.pos >= 9. && !self.pull_until_ready(cn) {
+                self.ended = true;
+                break;
+            }
+            let i = self.pos as usize;
+            let t = self.pos - i as f32;
+            *l = cubic(&self.left[i - 1..i + 3], t);
+            *r = cubic(&self.right[i - 1..i + 3], t);
+            self.pos += self.ratio;
+        }
+        let gain = (self.ratio / Self::STOP_SPEED).min(1.);
+        let left = Sample::new(left) * gain;
+        if self.stereo {
+            Some(Frame::stereo(left, Sample::new(right) * gain))
+        } else {
+            Some(Frame::mono(left))
+        }
+    }
+}
+
+/// Catmull-Rom interpolation between the 2nd and 3rd of the given 4 points.
+fn cubic(points: &[f32], t: f32) -> f32 {
+    let &[xm1, x0, x1, x2] = points else {
+        return 0.;
+    };
+    let c1 = 0.5 * (x1 - xm1);
+    let c2 = F32Ext::mul_add(
+        -2.5,
+        x0,
+        F32Ext::mul_add(2., x1, F32Ext::mul_add(-0.5, x2, xm1)),
+    );
+    let c3 = F32Ext::mul_add(0.5, x2 - xm1, 1.5 * (x0 - x1));
+    F32Ext::mul_add(F32Ext::mul_add(F32Ext::mul_add(c3, t, c2), t, c1), t, x0)
+}
+
+/// Play the children faster or slower without changing the pitch.
+///
+/// Uses [WSOLA]: the input is cut into short overlapping segments which are
+/// spread further apart or squeezed closer together. Each segment is picked
+/// from around its nominal position to best match the previous one,
+/// which avoids phase cancellation. Params:
+///
+/// 0. Tempo: 1 is the normal tempo, 0.5 is twice slower, 2 is twice faster.
+///    Supported values are from 0.25 to 4.
+///
+/// [WSOLA]: https://www.mdpi.com/2076-3417/6/2/57
+pub struct TimeStretch {
+    tempo: f32,
+    window: Vec<f32>,
+    input_left: Vec<f32>,
+    input_right: Vec<f32>,
+    output_left: Vec<f32>,
+    output_right: Vec<f32>,
+    /// The nominal position of the next segment in the input buffer.
+    analysis: f32,
+    /// The position in the input buffer that naturally continues the previous segment.
+    natural: Option<usize>,
+    /// The position in the input buffer where the children have ended.
+    end: Option<usize>,
+    /// The read position in the output buffer.
+    out_pos: usize,
+    stereo: bool,
+}
+
+impl TimeStretch {
+    /// The length of each segment (in samples).
+    const SIZE: usize = 512;
+    /// The distance between segments in the output (in samples).
+    const HOP: usize = Self::SIZE / 2;
+    /// How far from the nominal position a segment can be picked (in samples).
+    const TOLERANCE: usize = 64;
+    /// Only every Nth sample is used when comparing segments, to save CPU time.
+    const DECIMATION: usize = 4;
+
+    #[must_use]
+    pub fn new(tempo: f32) -> Self {
+        // Periodic Hann window, sums up to exactly 1 with 50% overlap.
+        let window = (0..Self::SIZE)
+            .map(|i| {
+                let angle = core::f32::consts::TAU * i as f32 / Self::SIZE as f32;
+                F32Ext::mul_add(-0.5, F32Ext::cos(angle), 0.5)
+            })
+            .collect();
+        let mut res = Self {
+            tempo: tempo.clamp(0.25, 4.),
+            window,
+            input_left: Vec::new(),
+            input_right: Vec::new(),
+            output_left: vec![0.; Self::SIZE],
+            output_right: vec![0.; Self::SIZE],
+            analysis: 0.,
+            natural: None,
+            end: None,
+            out_pos: 0,
+            stereo: false,
+        };
+        res.reset();
+        res
+    }
+
+    /// Pull frames from the children until the input buffer has the given length.
+    ///
+    /// If the children end, the buffer is padded with silence.
+    fn fill_input(&mut self, cn: &mut [Node], len: usize) {
+        while self.input_left.len() < len {
+            if self.end.is_none()
+                && let Some(f) = Mix::new().process_children(cn)
+            {
+                self.input_left.extend_from_slice(f.left.as_array());
+                let right = f.right.as_ref().unwrap_or(&f.left);
+                self.input_right.extend_from_slice(right.as_array());
+                self.stereo = f.right.is_some();
+                continue;
+            }
+            if self.end.is_none() {
+                self.end = Some(self.input_left.len());
+            }
+            self.input_left.resize(len, 0.);
+            self.input_right.resize(len, 0.);
+        }
+    }
+
+    /// Find the segment around the nominal position that best continues the previous one.
+    fn find_segment(&self, nominal: usize) -> usize {
+        let Some(natural) = self.natural else {
+            return nominal;
+        };
+        let mono = |i: usize| self.input_left[i] + self.input_right[i];
+        let mut best = nominal;
+        let mut best_corr = f32::MIN;
+        for pos in nominal - Self::TOLERANCE..=nominal + Self::TOLERANCE {
+            let mut corr = 0.;
+            for k in (0..Self::SIZE).step_by(Self::DECIMATION) {
+                corr = F32Ext::mul_add(mono(pos + k), mono(natural + k), corr);
+            }
+            if corr > best_corr {
+                best_corr = corr;
+                best = pos;
+            }
+        }
+        best
+    }
+
+    /// Add the next segment into the output buffer.
+    ///
+    /// Returns false if there is no input left.
+    #[expect(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn hop(&mut self, cn: &mut [Node]) -> bool {
+        let nominal = self.analysis as usize;
+        if let Some(end) = self.end
+            && nominal >= end
+        {
+            return false;
+        }
+        let mut needed = nominal + Self::TOLERANCE + Self::SIZE;
+        if let Some(natural) = self.natural {
+            needed = needed.max(natural + Self::SIZE);
+        }
+        self.fill_input(cn, needed);
+
+        let pos = self.find_segment(nominal);
+        for (out, pair) in [
+            (&mut self.output_left, &self.input_left),
+            (&mut self.output_right, &self.input_right),
+        ] {
+            out.copy_within(Self::HOP.., 0);
+            out[Self::SIZE - Self::HOP..].fill(0.);
+            let segment = &pair[pos..pos + Self::SIZE];
+            for ((o, s), w) in out.iter_mut().zip(segment).zip(&self.window) {
+                *o = F32Ext::mul_add(*s, *w, *o);
+            }
+        }
+        self.analysis += self.tempo * Self::HOP as f32;
+
+        // Drop the input that won't be needed anymore.
+        let keep_from = (self.analysis as usize - Self::TOLERANCE).min(pos + Self::HOP);
+        self.input_left.drain(..keep_from);
+        self.input_right.drain(..keep_from);
+        self.analysis -= keep_from as f32;
+        self.natural = Some(pos + Self::HOP - keep_from);
+        self.end = self.end.map(|end| end.saturating_sub(keep_from));
+        true
+    }
+}
+
+impl Processor for TimeStretch {
+    fn reset(&mut self) {
+        // Start with silence before the input so that the first segment
+        // can be picked from anywhere in the tolerance range.
+        self.input_left.clear();
+        self.input_left.resize(Self::TOLERANCE, 0.);
+        self.input_right.clear();
+        self.input_right.resize(Self::TOLERANCE, 0.);
+        self.output_left.fill(0.);
+        self.output_right.fill(0.);
+        self.analysis = Self::TOLERANCE as f32;
+        self.natural = None;
+        self.end = None;
+        self.out_pos = Self::HOP;
+    }
+
+    fn set(&mut self, param: u8, val: f32) {
+        if param == 0 {
+            self.tempo = val.clamp(0.25, 4.);
+        }
+    }
+
+    fn process_children(&mut self, cn: &mut [Node]) -> Option<Frame> {
+        if self.out_pos >= Self::HOP {
+            if !self.hop(cn) {
+                return None;
+            }
+            self.out_pos = 0;
+        }
+        let range = self.out_pos..self.out_pos + 8;
+        self.out_pos += 8;
+        let left = Sample::new(self.output_left[range.clone()].try_into().ok()?);
+        if self.stereo {
+            let right = Sample::new(self.output_right[range].try_into().ok()?);
+            Some(Frame::stereo(left, right))
+        } else {
+            Some(Frame::mono(left))
+        }
+    }
+}
+
+/// Multiply the first child by the second one (the carrier).
+///
+/// With zero bias, it's a ring modulator producing metallic and robotic sounds.
+/// With a non-zero bias, it's amplitude modulation: the carrier only partially
+/// changes the volume. For example, bias of 0.5 makes the volume swing between
+/// 0 and 1 with the carrier.
+///
+/// Instead of the second child, a built-in sine oscillator can be used
+/// as the carrier, see [`RingMod::new_carrier`]. Any other children are ignored.
+///
+/// The output ends when either the first child or the carrier child ends.
+/// If there is no carrier child (and no built-in carrier), the first child
+/// is passed through unchanged. Params:
+///
+/// 0. Bias: from 0 (ring modulation) to 1 (no modulation).
+/// 1. Frequency of the built-in carrier (in Hz).
+pub struct RingMod {
+    /// The phase step of the built-in carrier, if it's used.
+    step: Option<f32>,
+    phase: f32,
+    bias: f32,
+}
+
+impl RingMod {
+    /// Use the second child as the carrier.
+    ///
+    /// Without the second child, the input is not modulated.
+    #[must_use]
+    pub const fn new(bias: f32) -> Self {
+        Self {
+            step: None,
+            phase: 0.,
+            bias,
+        }
+    }
+
+    /// Use a built-in sine oscillator with the given frequency as the carrier.
+    #[must_use]
+    pub const fn new_carrier(freq: f32, bias: f32) -> Self {
+        Self {
+            step: Some(freq * SAMPLE_DURATION),
+            phase: 0.,
+            bias,
+        }
+    }
+
+    fn carrier(&mut self, cn: &mut [Node]) -> Option<Frame> {
+        let Some(step) = self.step else {
+            // Without the carrier child, use a constant 1 carrier
+            // which doesn't change the input, whatever the bias is.
+            let constant = || Some(Frame::mono(Sample::ONE));
+            return cn.get_mut(1).map_or_else(constant, Node::next_frame);
+        };
+        let mut phases = [0f32; 8];
+        for p in &mut phases {
+            *p = self.phase;
+            self.phase = F32Ext::fract(self.phase + step);
+        }
+        let s = (Sample::new(phases) * Sample::TAU).sin();
+        Some(Frame::mono(s))
+    }
+}
+
+impl Processor for RingMod {
+    fn reset(&mut self) {
+        self.phase = 0.;
+    }
+
+    fn set(&mut self, param: u8, val: f32) {
+        match param {
+            0 => self.bias = val.clamp(0., 1.),
+            1 if self.step.is_some() => self.step = Some(val * SAMPLE_DURATION),
+            _ => {}
+        }
+    }
+
+    fn process_children(&mut self, cn: &mut [Node]) -> Option<Frame> {
+        let input = cn.first_mut()?.next_frame()?;
+        let carrier = self.carrier(cn)?;
+        let depth = Sample::splat(1. - self.bias);
+        let bias = Sample::splat(self.bias);
+        let left_gain = carrier.left.mul_add(depth, bias);
+        let left = input.left * left_gain;
+        let right = match (input.right, carrier.right) {
+            (None, None) => None,
+            (Some(r), None) => Some(r * left_gain),
+            (r, Some(c)) => Some(r.unwrap_or(input.left) * c.mul_add(depth, bias)),
+        };
+        Some(Frame { left, right })
+    }
+}
+
+/// Blend between the first two children using an equal-power curve.
+///
+/// A fully faded-out child is not pulled at all, so it pauses until it's
+/// faded back in. With sync enabled, it's still pulled (and the result discarded)
+/// to stay in sync with the other child. Params:
+///
+/// 0. Position: 0 is only the first child, 1 is only the second child.
+/// 1. Sync: if 1, keep pulling a faded-out child.
+pub struct Crossfade {
+    position: f32,
+    /// The position on the previous frame, used to interpolate changes.
+    prev: f32,
+    sync: bool,
+}
+
+impl Crossfade {
+    #[must_use]
+    pub const fn new(position: f32) -> Self {
+        let position = position.clamp(0., 1.);
+        Self {
+            position,
+            prev: position,
+            sync: false,
+        }
+    }
+
+    /// Keep pulling a faded-out child to stay in sync with the other one.
+    pub const fn set_sync(&mut self, sync: bool) {
+        self.sync = sync;
+    }
+
+    /// Pull the next frame from the child if it's audible or sync is enabled.
+    fn pull(&self, node: Option<&mut Node>, audible: bool) -> Option<Frame> {
+        if !audible && !self.sync {
+            return None;
+        }
+        let frame = node?.next_frame();
+        if audible { frame } else { None }
+    }
+}
+
+/// The gains of the two inputs for the given crossfade position.
+fn crossfade_gains(position: f32) -> (f32, f32) {
+    if position <= 0. {
+        return (1., 0.);
+    }
+    if position >= 1. {
+        return (0., 1.);
+    }
+    let (sin, cos) = F32Ext::sin_cos(position * core::f32::consts::FRAC_PI_2);
+    (cos, sin)
+}
+
+/// Multiply both channels of the frame by the given gains.
+fn scale_frame(f: &Frame, gain: Sample) -> Frame {
+    Frame {
+        left: f.left * gain,
+        right: f.right.map(|s| s * gain),
+    }
+}
+
+impl Processor for Crossfade {
+    fn reset(&mut self) {
+        self.prev = self.position;
+    }
+
+    fn set(&mut self, param: u8, val: f32) {
+        match param {
+            0 => self.position = val.clamp(0., 1.),
+            1 => self.sync = val >= 0.5,
+            _ => {}
+        }
+    }
+
+    fn process_children(&mut self, cn: &mut [Node]) -> Option<Frame> {
+        let (start, end) = (self.prev, self.position);
+        self.prev = end;
+        let mut iter = cn.iter_mut();
+        let first = self.pull(iter.next(), start < 1. || end < 1.);
+        let second = self.pull(iter.next(), start > 0. || end > 0.);
+
+        let (start_first, start_second) = crossfade_gains(start);
+        let (end_first, end_second) = crossfade_gains(end);
+        let ramp = |from: f32, to: f32| {
+            let mut gains = [0f32; 8];
+            for (i, gain) in gains.iter_mut().enumerate() {
+                *gain = F32Ext::mul_add(to - from, (i + 1) as f32 / 8., from);
+            }
+            Sample::new(gains)
+        };
+        let first = first.map(|f| scale_frame(&f, ramp(start_first, end_first)));
+        let second = second.map(|f| scale_frame(&f, ramp(start_second, end_second)));
+        match (first, second) {
+            (Some(a), Some(b)) => Some(a + b),
+            (a, b) => a.or(b),
+        }
+    }
+}
+
+/// How the volume of a [`Spatializer`] emitter goes down with the distance.
+///
+/// Below the min distance, the volume is not changed. Above the max distance,
+/// the volume doesn't go down any further.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Attenuation {
+    /// Go down linearly, reaching silence at the max distance (with rolloff 1).
+    Linear,
+    /// Go down inversely proportional to the distance, like in the real world.
+    Inverse,
+    /// Go down exponentially, with rolloff being the exponent.
+    Exponential,
+}
+
+impl Attenuation {
+    fn from_param(val: f32) -> Self {
+        if val < 0.5 {
+            Self::Linear
+        } else if val < 1.5 {
+            Self::Inverse
+        } else {
+            Self::Exponential
+        }
+    }
+
+    /// Calculate the gain for the given distance.
+    fn gain(self, distance: f32, min: f32, max: f32, rolloff: f32) -> f32 {
+        let min = min.max(1e-3);
+        let max = max.max(min);
+        let distance = distance.clamp(min, max);
+        match self {
+            Self::Linear => {
+                if max <= min {
+                    return 1.;
+                }
+                let t = (distance - min) / (max - min);
+                F32Ext::mul_add(-rolloff, t, 1.).clamp(0., 1.)
+            }
+            Self::Inverse => min / F32Ext::mul_add(rolloff, distance - min, min),
+            Self::Exponential => F32Ext::powf(distance / min, -rolloff),
+        }
+    }
+}
+
+/// Place the sound in 2D or 3D space relative to the listener.
+///
+/// The volume goes down with the distance (see [`Attenuation`]),
+/// high frequencies are absorbed by the air, and the sound is panned
+/// to the side where the emitter is. Optionally, the pitch changes
+/// when the emitter moves towards or away from the listener (the Doppler effect).
+///
+/// The X axis goes to the right and the Y axis goes forward from the listener.
+/// For 3D, the Z axis goes up. Distances are in arbitrary units (meters are
+/// assumed for the Doppler effect). Stereo input is mixed into mono. Params:
+///
+/// 0. Listener X position.
+/// 1. Listener Y position.
+/// 2. Listener Z position.
+/// 3. Emitter X position.
+/// 4. Emitter Y position.
+/// 5. Emitter Z position.
+/// 6. Emitter X velocity (in units per second), relative to the listener.
+/// 7. Emitter Y velocity.
+/// 8. Emitter Z velocity.
+/// 9. Min distance: the emitter is at the full volume when closer than that.
+/// 10. Max distance: the emitter doesn't get any quieter when further than that.
+/// 11. Rolloff: how fast the volume goes down with the distance. Default is 1.
+/// 12. Attenuation model: 0 is [`Attenuation::Linear`], 1 is [`Attenuation::Inverse`],
+///     and 2 is [`Attenuation::Exponential`].
+/// 13. Air absorption: the low-pass cutoff frequency (in Hz) at the max distance.
+///     The cutoff goes up to 20 kHz at the min distance.
+/// 14. Doppler factor: 0 disables the Doppler effect, 1 is realistic.
+pub struct Spatializer {
+    listener: [f32; 3],
+    emitter: [f32; 3],
+    velocity: [f32; 3],
+    min_distance: f32,
+    max_distance: f32,
+    rolloff: f32,
+    model: Attenuation,
+    absorption: f32,
+    doppler: f32,
+    /// Resamples the children for the Doppler effect.
+    speed: Speed,
+    /// The state of the air absorption low-pass filter.
+    lowpass: f32,
+    /// The channel gains on the previous frame, used to interpolate changes.
+    prev: Option<(f32, f32)>,
+}
+
+impl Spatializer {
+    /// The speed of sound in the air (in meters per second).
+    const SPEED_OF_SOUND: f32 = 343.;
+    /// The cutoff frequency of the air absorption filter at the min distance.
+    const MAX_CUTOFF: f32 = 20_000.;
+
+    #[must_use]
+    pub const fn new(model: Attenuation, min_distance: f32, max_distance: f32) -> Self {
+        Self {
+            listener: [0.; 3],
+            emitter: [0.; 3],
+            velocity: [0.; 3],
+            min_distance,
+            max_distance,
+            rolloff: 1.,
+            model,
+            absorption: 5_000.,
+            doppler: 0.,
+            speed: Speed::new(1.),
+            lowpass: 0.,
+            prev: None,
+        }
+    }
+
+    /// Calculate the left and right channel gains.
+    fn gains(&self, offset: [f32; 3], distance: f32) -> (f32, f32) {
+        let gain = self
+            .model
+            .gain(distance, self.min_distance, self.max_distance, self.rolloff);
+        let pan = if distance > 1e-6 {
+            offset[0] / distance
+        } else {
+            0.
+        };
+        let (left, right) = pan_weights(pan);
+        (left * gain, right * gain)
+    }
+
+    /// Calculate the pole of the air absorption filter.
+    fn lowpass_pole(&self, distance: f32) -> f32 {
+        let range = self.max_distance - self.min_distance;
+        if range <= 0. {
+            return 0.;
+        }
+        let t = ((distance - self.min_distance) / range).clamp(0., 1.);
+        let far = self.absorption.clamp(10., Self::MAX_CUTOFF);
+        let cutoff = Self::MAX_CUTOFF * F32Ext::powf(far / Self::MAX_CUTOFF, t);
+        dc_blocker_pole(cutoff)
+    }
+
+    /// Calculate the playback speed of the emitter for the Doppler effect.
+    fn doppler_ratio(&self, offset: [f32; 3], distance: f32) -> f32 {
+        if self.doppler == 0. || distance <= 1e-6 {
+            return 1.;
+        }
+        // The speed with which the emitter moves away from the listener.
+        let mut receding = 0.;
+        for (o, v) in offset.iter().zip(&self.velocity) {
+            receding = F32Ext::mul_add(*o, *v, receding);
+        }
+        let receding = receding / distance * self.doppler;
+        let ratio = Self::SPEED_OF_SOUND / (Self::SPEED_OF_SOUND + receding).max(1.);
+        ratio.clamp(0.5, 2.)
+    }
+}
+
+impl Processor for Spatializer {
+    fn reset(&mut self) {
+        self.speed.reset();
+        self.lowpass = 0.;
+        self.prev = None;
+    }
+
+    fn set(&mut self, param: u8, val: f32) {
+        match param {
+            0..=2 => self.listener[param as usize] = val,
+            3..=5 => self.emitter[param as usize - 3] = val,
+            6..=8 => self.velocity[param as usize - 6] = val,
+            9 => self.min_distance = val.max(0.),
+            10 => self.max_distance = val.max(0.),
+            11 => self.rolloff = val.max(0.),
+            12 => self.model = Attenuation::from_param(val),
+            13 => self.absorption = val,
+            14 => self.doppler = val.max(0.),
+            _ => {}
+        }
+    }
+
+    fn process_children(&mut self, cn: &mut [Node]) -> Option<Frame> {
+        let mut offset = [0f32; 3];
+        for ((o, e), l) in offset.iter_mut().zip(&self.emitter).zip(&self.listener) {
+            *o = e - l;
+        }
+        let squared = offset.iter().map(|o| o * o).sum::<f32>();
+        let distance = F32Ext::sqrt(squared);
+
+        self.speed.set(0, self.doppler_ratio(offset, distance));
+        let f = self.speed.process_children(cn)?;
+        let mono = MonoSum::new().process_frame(f)?.left;
+
+        let pole = self.lowpass_pole(distance);
+        let mut samples = mono.to_array();
+        for s in &mut samples {
+            self.lowpass = F32Ext::mul_add(pole, self.lowpass - *s, *s);
+            *s = self.lowpass;
+        }
+        let mono = Sample::new(samples);
+
+        let (left, right) = self.gains(offset, distance);
+        let (prev_left, prev_right) = self.prev.unwrap_or((left, right));
+        self.prev = Some((left, right));
+        let mut left_gains = [0f32; 8];
+        let mut right_gains = [0f32; 8];
+        for (i, (l, r)) in left_gains.iter_mut().zip(&mut right_gains).enumerate() {
+            let t = (i + 1) as f32 / 8.;
+            *l = F32Ext::mul_add(left - prev_left, t, prev_left);
+            *r = F32Ext::mul_add(right - prev_right, t, prev_right);
+        }
+        Some(Frame::stereo(
+            mono * Sample::new(left_gains),
+            mono * Sample::new(right_gains),
+        ))
+    }
+}
+
+// https://www.masteringbox.com/learn/audio-effects
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: Sample, expected: f32) {
+        for s in actual.to_array() {
+            assert!((s - expected).abs() < 1e-3, "{s} != {expected}");
+        }
+    }
+
+    #[test]
+    fn limiter_mono_stereo_switch() {
+        let mono = Frame::mono(Sample::splat(0.5));
+        let stereo = Frame::stereo(Sample::splat(0.5), Sample::splat(-0.5));
+        let mut limiter = Limiter::new(0., 8, 100.);
+        limiter.process_frame(mono.clone()).unwrap();
+
+        // The delayed right channel comes from the mono input.
+        let f = limiter.process_frame(stereo).unwrap();
+        assert_close(f.left, 0.5);
+        assert_close(f.right.unwrap(), 0.5);
+
+        // The delayed stereo input is emitted after the input goes mono.
+        let f = limiter.process_frame(mono.clone()).unwrap();
+        assert_close(f.left, 0.5);
+        assert_close(f.right.unwrap(), -0.5);
+        let f = limiter.process_frame(mono).unwrap();
+        assert!(f.right.is_none());
+    }
+
+    #[test]
+    fn convolution_mono_stereo_switch() {
+        // A pure delay by 10 samples.
+        let mut ir = [0f32; 11];
+        ir[10] = 1.;
+        let mono = Frame::mono(Sample::splat(0.5));
+        let stereo = Frame::stereo(Sample::splat(0.5), Sample::splat(-0.5));
+        let mut conv = Convolution::new(&ir);
+        conv.process_frame(mono.clone()).unwrap();
+        conv.process_frame(mono.clone()).unwrap();
+
+        // The delayed right channel comes from the mono input.
+        let f = conv.process_frame(stereo).unwrap();
+        assert_close(f.right.unwrap(), 0.5);
+
+        // The tail of the stereo input is emitted after the input goes mono.
+        let f = conv.process_frame(mono.clone()).unwrap();
+        let right = f.right.unwrap().to_array();
+        assert!((right[4] + 0.5).abs() < 1e-6, "{right:?}");
+        for _ in 0..20 {
+            conv.process_frame(mono.clone()).unwrap();
+        }
+        assert!(conv.process_frame(mono).unwrap().right.is_none());
+    }
+
+    #[test]
+    fn vibrato_mono_stereo_switch() {
+        let loud = Frame::stereo(Sample::splat(0.9), Sample::splat(0.9));
+        let silent = Frame::stereo(Sample::ZERO, Sample::ZERO);
+        let mut vibrato = Vibrato::new(5., 500.);
+        for _ in 0..200 {
+            vibrato.process_frame(loud.clone()).unwrap();
+        }
+        for _ in 0..200 {
+            vibrato.process_frame(Frame::mono(Sample::ZERO)).unwrap();
+        }
+        // No stale audio is left in the right channel.
+        let f = vibrato.process_frame(silent).unwrap();
+        assert_close(f.right.unwrap(), 0.);
+    }
+
+    #[test]
+    fn pitch_shift_mono_stereo_switch() {
+        let loud = Frame::stereo(Sample::splat(0.9), Sample::splat(0.9));
+        let silent = Frame::stereo(Sample::ZERO, Sample::ZERO);
+        let mut shift = PitchShift::new(3.);
+        for _ in 0..400 {
+            shift.process_frame(loud.clone()).unwrap();
+        }
+        for _ in 0..400 {
+            shift.process_frame(Frame::mono(Sample::ZERO)).unwrap();
+        }
+        // No stale audio is left in the right channel.
+        let f = shift.process_frame(silent).unwrap();
+        assert_close(f.right.unwrap(), 0.);
+    }
+
+    #[test]
+    fn gate_hold_after_hysteresis() {
+        let main = Frame::mono(Sample::splat(1.));
+        let key = |level: f32| Frame::mono(Sample::splat(level));
+        // Opens above 0.1 and closes below 0.07.
+        let mut gate = Gate::new(-20., 1., 100, 10.);
+        gate.apply(&main, &key(1.));
+        // The level stays between the two thresholds for longer than the hold.
+        for _ in 0..200 {
+            let f = gate.apply(&main, &key(0.08));
+            assert_close(f.left, 1.);
+        }
+        // The gate closes a few samples after the key goes silent,
+        // but the hold keeps it open for 100 more samples.
+        for _ in 0..8 {
+            let f = gate.apply(&main, &key(0.));
+            assert_close(f.left, 1.);
+        }
+    }
+
+    #[test]
+    fn concat_lookahead_capped() {
+        let max = Concat::MAX_LOOKAHEAD;
+        let mut concat = Concat::new_crossfade(Position::MAX);
+        assert_eq!(concat.fade_out, max);
+        assert_eq!(concat.overlap, max);
+        concat.set(1, 1e9);
+        concat.set(2, 1e9);
+        assert_eq!(concat.lookahead(), max.div_ceil(8) as usize);
+    }
+
+    #[test]
+    fn mid_side_roundtrip() {
+        let frame = Frame::stereo(Sample::splat(0.8), Sample::splat(-0.2));
+        let encoded = MidSideEncode::new().process_frame(frame).unwrap();
+        assert_close(encoded.left, 0.3);
+        assert_close(encoded.right.unwrap(), 0.5);
+        let decoded = MidSideDecode::new().process_frame(encoded).unwrap();
+        assert_close(decoded.left, 0.8);
+        assert_close(decoded.right.unwrap(), -0.2);
+    }
+
+    #[test]
+    fn width() {
+        let frame = Frame::stereo(Sample::splat(0.8), Sample::splat(-0.2));
+        let f = Width::new(0.).process_frame(frame.clone()).unwrap();
+        assert_close(f.left, 0.3);
+        assert_close(f.right.unwrap(), 0.3);
+        let f = Width::new(1.).process_frame(frame).unwrap();
+        assert_close(f.left, 0.8);
+        assert_close(f.right.unwrap(), -0.2);
+    }
+
+    #[test]
+    fn balance_mono() {
+        let frame = Frame::mono(Sample::splat(0.5));
+        let f = Balance::new(0.).process_frame(frame.clone()).unwrap();
+        assert_close(f.left, 0.5);
+        assert_close(f.right.unwrap(), 0.5);
+        let f = Balance::new(0.5).process_frame(frame.clone()).unwrap();
+        assert_close(f.left, 0.25);
+        assert_close(f.right.unwrap(), 0.5);
+        let f = Balance::new(-1.).process_frame(frame).unwrap();
+        assert_close(f.left, 0.5);
+        assert_close(f.right.unwrap(), 0.);
+    }
+
+    #[test]
+    fn mono_sum() {
+        let frame = Frame::stereo(Sample::splat(0.8), Sample::splat(-0.2));
+        let f = MonoSum::new().process_frame(frame).unwrap();
+        assert_close(f.left, 0.3);
+        assert!(f.right.is_none());
+        let frame = Frame::mono(Sample::splat(0.5));
+        let f = MonoSum::new().process_frame(frame).unwrap();
+        assert_close(f.left, 0.5);
+        assert!(f.right.is_none());
+    }
+
+    #[test]
+    fn attenuation() {
+        for model in [
+            Attenuation::Linear,
+            Attenuation::Inverse,
+            Attenuation::Exponential,
+        ] {
+            let gain = |distance: f32| model.gain(distance, 1., 10., 1.);
+            assert!((gain(0.5) - 1.).abs() < 1e-3, "{model:?}");
+            assert!((gain(1.) - 1.).abs() < 1e-3, "{model:?}");
+            let expected = if model == Attenuation::Linear {
+                0.
+            } else {
+                0.1
+            };
+            assert!((gain(10.) - expected).abs() < 1e-3, "{model:?}");
+            assert!((gain(20.) - expected).abs() < 1e-3, "{model:?}");
+        }
+    }
+
+    #[test]
+    fn spatializer_pan() {
+        let spatializer = Spatializer::new(Attenuation::Inverse, 1., 10.);
+        let (left, right) = spatializer.gains([1., 0., 0.], 1.);
+        assert!(left < 0.01, "{left}");
+        assert!((right - 1.).abs() < 1e-3, "{right}");
+        let (left, right) = spatializer.gains([-1., 0., 0.], 1.);
+        assert!((left - 1.).abs() < 1e-3, "{left}");
+        assert!(right < 0.01, "{right}");
+    }
+
+    #[test]
+    fn spatializer_doppler() {
+        let mut spatializer = Spatializer::new(Attenuation::Inverse, 1., 10.);
+        spatializer.set(14, 1.);
+        // The emitter is in front of the listener and moves towards it.
+        spatializer.set(7, -34.3);
+        let ratio = spatializer.doppler_ratio([0., 10., 0.], 10.);
+        assert!((ratio - 343. / 308.7).abs() < 1e-3, "{ratio}");
+        // The emitter moves away.
+        spatializer.set(7, 34.3);
+        let ratio = spatializer.doppler_ratio([0., 10., 0.], 10.);
+        assert!((ratio - 343. / 377.3).abs() < 1e-3, "{ratio}");
+        // Doppler is disabled.
+        spatializer.set(14, 0.);
+        assert!((spatializer.doppler_ratio([0., 10., 0.], 10.) - 1.).abs() < 1e-6);
+    }
+
+    #[test]
+    fn pan_mono() {
+        let frame = Frame::mono(Sample::splat(1.));
+        let mut pan = Pan::new(-1.);
+        let f = pan.process_frame(frame.clone()).unwrap();
+        assert_close(f.left, 1.);
+        assert_close(f.right.unwrap(), 0.);
+
+        pan.set(0, 1.);
+        let f = pan.process_frame(frame.clone()).unwrap();
+        assert_close(f.left, 0.);
+        assert_close(f.right.unwrap(), 1.);
+
+        pan.set(0, 0.);
+        let f = pan.process_frame(frame).unwrap();
+        assert_close(f.left, core::f32::consts::FRAC_1_SQRT_2);
+        assert_close(f.right.unwrap(), core::f32::consts::FRAC_1_SQRT_2);
+    }
+
+    #[test]
+    fn pan_stereo() {
+        let frame = Frame::stereo(Sample::splat(1.), Sample::splat(0.5));
+        let mut pan = Pan::new(1.);
+        let f = pan.process_frame(frame.clone()).unwrap();
+        assert_close(f.left, 0.);
+        assert_close(f.right.unwrap(), 0.5);
+
+        // In the rotate mode, the image is unchanged at the center.
+        let mut pan = Pan::new_rotate(0.);
+        let f = pan.process_frame(frame.clone()).unwrap();
+        assert_close(f.left, 1.);
+        assert_close(f.right.unwrap(), 0.5);
+
+        // ...and both channels are moved to the right side.
+        pan.set(0, 1.);
+        let f = pan.process_frame(frame).unwrap();
+        assert_close(f.left, core::f32::consts::FRAC_1_SQRT_2);
+        assert_close(f.right.unwrap(), 0.5 + core::f32::consts::FRAC_1_SQRT_2);
+    }
+}